@@ -0,0 +1,138 @@
+//! Compares looking up collisions through the spatial grid
+//! against checking every projectile against every enemy.
+//!
+//! Run with `cargo bench --bench collisions`.
+
+// The game is a binary, so the modules being measured are
+// pulled in directly. Parts of them only the game or their
+// tests use would otherwise be dead code here
+#[allow(dead_code)]
+#[path = "../src/hitbox.rs"]
+mod hitbox;
+#[allow(dead_code)]
+#[path = "../src/spatial.rs"]
+mod spatial;
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use hitbox::{Collider, Hitbox};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use spatial::SpatialGrid;
+use tetra::math::Vec2;
+
+/// The same numbers the game uses
+const ARENA_SIZE: f32 = 800.0;
+const CELL_SIZE: f32 = 64.0;
+const ENEMY_HITBOX: Hitbox =
+    Hitbox::aabb(36.0, 44.0, Vec2 { x: 0.0, y: 2.0 });
+const FIREBALL_HITBOX: Hitbox =
+    Hitbox::circle(9.0, Vec2 { x: 0.0, y: 0.0 });
+
+const ENEMY_COUNT: usize = 200;
+const PROJECTILE_COUNTS: [usize; 4] =
+    [100, 1_000, 5_000, 20_000];
+
+/// How many ticks each case is timed over
+const TICKS: u32 = 100;
+
+fn random_colliders(
+    rng: &mut SmallRng,
+    count: usize,
+    hitbox: Hitbox,
+) -> Vec<Collider> {
+    (0..count)
+        .map(|_| {
+            hitbox.at(Vec2::new(
+                rng.gen_range(0.0..ARENA_SIZE),
+                rng.gen_range(0.0..ARENA_SIZE),
+            ))
+        })
+        .collect()
+}
+
+/// The average time of a tick, and how many hits it found
+fn time_ticks(
+    mut tick: impl FnMut() -> usize,
+) -> (Duration, usize) {
+    let hits = tick();
+
+    let start = Instant::now();
+    for _ in 0..TICKS {
+        black_box(tick());
+    }
+
+    (start.elapsed() / TICKS, hits)
+}
+
+fn main() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let enemies =
+        random_colliders(&mut rng, ENEMY_COUNT, ENEMY_HITBOX);
+    let mut grid =
+        SpatialGrid::new(ARENA_SIZE, ARENA_SIZE, CELL_SIZE);
+
+    println!(
+        "{ENEMY_COUNT} enemies, average of {TICKS} ticks\n"
+    );
+    println!(
+        "{:>11} {:>12} {:>12} {:>8}",
+        "projectiles", "naive", "grid", "speedup"
+    );
+
+    for count in PROJECTILE_COUNTS {
+        let projectiles =
+            random_colliders(&mut rng, count, FIREBALL_HITBOX);
+
+        let (naive, naive_hits) = time_ticks(|| {
+            projectiles
+                .iter()
+                .map(|projectile| {
+                    enemies
+                        .iter()
+                        .filter(|enemy| {
+                            enemy.intersects(projectile)
+                        })
+                        .count()
+                })
+                .sum()
+        });
+
+        // The grid is rebuilt on every tick, just like in the
+        // game
+        let (gridded, grid_hits) = time_ticks(|| {
+            grid.rebuild(enemies.iter().copied());
+
+            let mut hits = 0;
+            for projectile in &projectiles {
+                grid.for_each_intersecting(projectile, |_| {
+                    hits += 1
+                });
+            }
+            hits
+        });
+
+        assert_eq!(
+            naive_hits, grid_hits,
+            "the grid should find the same hits"
+        );
+        assert_eq!(
+            projectiles.iter().any(|projectile| enemies
+                .iter()
+                .any(|enemy| enemy.intersects(projectile))),
+            projectiles
+                .iter()
+                .any(|projectile| grid
+                    .any_intersecting(projectile)),
+        );
+
+        println!(
+            "{count:>11} {:>12?} {:>12?} {:>7.1}x",
+            naive,
+            gridded,
+            naive.as_secs_f64() / gridded.as_secs_f64()
+        );
+    }
+}
@@ -5,9 +5,13 @@ use tetra::{
     Context,
 };
 
-use crate::resources::{CANNONBALL, FIREBALL};
+use crate::{
+    oneoffanim::{EXPLOSION_FRAMES, SMOKE_FRAMES},
+    resources::{CANNONBALL, EXPLOSION, FIREBALL, SMOKE},
+};
 
 /// Animation for the player and grunts
+#[derive(Debug)]
 pub struct HumanoidAnimation {
     // Front-side walking animation
     pub frontside: Animation,
@@ -84,3 +88,39 @@ impl CannonballAnimation {
         )
     }
 }
+
+pub struct ExplosionAnimation;
+
+impl ExplosionAnimation {
+    pub fn build(ctx: &mut Context) -> Animation {
+        let explosion_sprite = Texture::from_encoded(
+            ctx, EXPLOSION,
+        )
+        .expect("Failed to load built-in explosion sprite");
+
+        Animation::new(
+            explosion_sprite,
+            Rectangle::row(0.0, 0.0, 64.0, 64.0)
+                .take(EXPLOSION_FRAMES as usize)
+                .collect(),
+            Duration::from_secs_f32(0.05),
+        )
+    }
+}
+
+pub struct SmokeAnimation;
+
+impl SmokeAnimation {
+    pub fn build(ctx: &mut Context) -> Animation {
+        let smoke_sprite = Texture::from_encoded(ctx, SMOKE)
+            .expect("Failed to load built-in smoke sprite");
+
+        Animation::new(
+            smoke_sprite,
+            Rectangle::row(0.0, 0.0, 64.0, 64.0)
+                .take(SMOKE_FRAMES as usize)
+                .collect(),
+            Duration::from_secs_f32(0.05),
+        )
+    }
+}
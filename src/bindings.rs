@@ -0,0 +1,598 @@
+//! What the player can do in a run, and the keys and gamepad
+//! buttons that do it.
+//!
+//! Every [`InputAction`] can be bound to several inputs at
+//! once, but an input only ever does one action. Menus aren't
+//! rebindable, and always go by the arrow keys, Enter and
+//! Escape.
+
+use std::{collections::BTreeMap, fmt};
+
+use tetra::{
+    input::{self, GamepadButton, Key},
+    Context,
+};
+
+use crate::gamepad::Gamepads;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    FireUp,
+    FireDown,
+    FireLeft,
+    FireRight,
+    Pause,
+    ToggleScaling,
+    ToggleOverlay,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 11] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::FireUp,
+        InputAction::FireDown,
+        InputAction::FireLeft,
+        InputAction::FireRight,
+        InputAction::Pause,
+        InputAction::ToggleScaling,
+        InputAction::ToggleOverlay,
+    ];
+
+    /// How the action is shown to the player
+    pub fn name(self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "Move up",
+            InputAction::MoveDown => "Move down",
+            InputAction::MoveLeft => "Move left",
+            InputAction::MoveRight => "Move right",
+            InputAction::FireUp => "Fire up",
+            InputAction::FireDown => "Fire down",
+            InputAction::FireLeft => "Fire left",
+            InputAction::FireRight => "Fire right",
+            InputAction::Pause => "Pause",
+            InputAction::ToggleScaling => "Change scaling",
+            InputAction::ToggleOverlay => "Debug overlay",
+        }
+    }
+
+    /// How the action is written in the config file
+    pub fn key(self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "move_up",
+            InputAction::MoveDown => "move_down",
+            InputAction::MoveLeft => "move_left",
+            InputAction::MoveRight => "move_right",
+            InputAction::FireUp => "fire_up",
+            InputAction::FireDown => "fire_down",
+            InputAction::FireLeft => "fire_left",
+            InputAction::FireRight => "fire_right",
+            InputAction::Pause => "pause",
+            InputAction::ToggleScaling => "toggle_scaling",
+            InputAction::ToggleOverlay => "toggle_overlay",
+        }
+    }
+
+    /// The opposite of [`InputAction::key`]
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|action| action.key() == key)
+    }
+
+    fn default_bindings(self) -> Vec<Binding> {
+        use Binding::{Button, Key as K};
+
+        match self {
+            InputAction::MoveUp => {
+                vec![K(Key::W), Button(GamepadButton::Up)]
+            }
+            InputAction::MoveDown => {
+                vec![K(Key::S), Button(GamepadButton::Down)]
+            }
+            InputAction::MoveLeft => {
+                vec![K(Key::A), Button(GamepadButton::Left)]
+            }
+            InputAction::MoveRight => {
+                vec![K(Key::D), Button(GamepadButton::Right)]
+            }
+            InputAction::FireUp => {
+                vec![K(Key::Up), Button(GamepadButton::Y)]
+            }
+            InputAction::FireDown => {
+                vec![K(Key::Down), Button(GamepadButton::A)]
+            }
+            InputAction::FireLeft => {
+                vec![K(Key::Left), Button(GamepadButton::X)]
+            }
+            InputAction::FireRight => {
+                vec![K(Key::Right), Button(GamepadButton::B)]
+            }
+            InputAction::Pause => vec![
+                K(Key::Escape),
+                K(Key::P),
+                Button(GamepadButton::Start),
+            ],
+            InputAction::ToggleScaling => vec![K(Key::F1)],
+            InputAction::ToggleOverlay => vec![K(Key::F3)],
+        }
+    }
+}
+
+/// A key or a button of the gamepad in use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(Key),
+    Button(GamepadButton),
+}
+
+/// Gamepad buttons are written with this in front, so that
+/// they can't be mistaken for keys of the same name
+const BUTTON_PREFIX: &str = "Pad ";
+
+impl Binding {
+    /// The opposite of how bindings are displayed
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix(BUTTON_PREFIX) {
+            Some(button) => BUTTONS
+                .iter()
+                .find(|other| format!("{other:?}") == button)
+                .map(|&button| Binding::Button(button)),
+            None => KEYS
+                .iter()
+                .find(|other| format!("{other:?}") == name)
+                .map(|&key| Binding::Key(key)),
+        }
+    }
+
+    fn is_down(
+        self,
+        ctx: &Context,
+        gamepads: &Gamepads,
+    ) -> bool {
+        match self {
+            Binding::Key(key) => input::is_key_down(ctx, key),
+            Binding::Button(button) => {
+                gamepads.is_button_down(ctx, button)
+            }
+        }
+    }
+
+    fn is_pressed(
+        self,
+        ctx: &Context,
+        gamepads: &Gamepads,
+    ) -> bool {
+        match self {
+            Binding::Key(key) => input::is_key_pressed(ctx, key),
+            Binding::Button(button) => {
+                gamepads.is_button_pressed(ctx, button)
+            }
+        }
+    }
+
+    /// The first key or button pressed since the last update,
+    /// if any
+    pub fn pressed(
+        ctx: &Context,
+        gamepads: &Gamepads,
+    ) -> Option<Self> {
+        input::get_keys_pressed(ctx)
+            .next()
+            .map(|&key| Binding::Key(key))
+            .or_else(|| {
+                gamepads
+                    .buttons_pressed(ctx)
+                    .next()
+                    .map(Binding::Button)
+            })
+    }
+}
+
+/// Shows the binding as it's written in the config file
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Button(button) => {
+                write!(f, "{BUTTON_PREFIX}{button:?}")
+            }
+        }
+    }
+}
+
+/// Which inputs do which action
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    /// The bindings of every action, in the order of
+    /// [`InputAction::ALL`]
+    actions: [Vec<Binding>; InputAction::ALL.len()],
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            actions: InputAction::ALL
+                .map(InputAction::default_bindings),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        &self.actions[action as usize]
+    }
+
+    /// Whether any input of `action` is held down
+    pub fn is_down(
+        &self,
+        ctx: &Context,
+        gamepads: &Gamepads,
+        action: InputAction,
+    ) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| binding.is_down(ctx, gamepads))
+    }
+
+    /// Whether any input of `action` was pressed since the last
+    /// update
+    pub fn is_pressed(
+        &self,
+        ctx: &Context,
+        gamepads: &Gamepads,
+        action: InputAction,
+    ) -> bool {
+        self.get(action)
+            .iter()
+            .any(|binding| binding.is_pressed(ctx, gamepads))
+    }
+
+    /// The action `binding` already does, if any
+    pub fn action_of(
+        &self,
+        binding: Binding,
+    ) -> Option<InputAction> {
+        InputAction::ALL
+            .iter()
+            .copied()
+            .find(|&action| self.get(action).contains(&binding))
+    }
+
+    /// Makes `binding` do `action`, and only that
+    pub fn bind(
+        &mut self,
+        action: InputAction,
+        binding: Binding,
+    ) {
+        for bindings in &mut self.actions {
+            bindings.retain(|&other| other != binding);
+        }
+
+        self.actions[action as usize].push(binding);
+    }
+
+    pub fn clear(&mut self, action: InputAction) {
+        self.actions[action as usize].clear();
+    }
+
+    /// Lists the bindings of `action`, as shown to the player
+    pub fn describe(&self, action: InputAction) -> String {
+        let bindings = self.get(action);
+        if bindings.is_empty() {
+            return "(none)".to_owned();
+        }
+
+        bindings
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Replaces the bindings of every action in the
+    /// `[bindings]` table of the config file, leaving the
+    /// others as they were. A key can't be taken from an
+    /// action that isn't listed, since that would quietly leave
+    /// it unbound
+    pub fn read_table(
+        &mut self,
+        table: &BTreeMap<String, Vec<String>>,
+    ) -> Result<(), String> {
+        let mut listed = Vec::new();
+        for (key, names) in table {
+            let action =
+                InputAction::from_key(key).ok_or_else(|| {
+                    format!(
+                    "unknown action `{key}`, expected one of: {}",
+                    InputAction::ALL.map(InputAction::key).join(", ")
+                )
+                })?;
+
+            self.clear(action);
+            listed.push((action, names));
+        }
+
+        let mut seen = Vec::new();
+        for (action, names) in listed {
+            for name in names {
+                let binding = Binding::from_name(name)
+                    .ok_or_else(|| {
+                        format!("unknown key or button `{name}`")
+                    })?;
+
+                if seen.contains(&binding) {
+                    return Err(format!(
+                        "`{name}` is bound twice"
+                    ));
+                }
+                seen.push(binding);
+
+                // Every listed action was cleared, so the
+                // binding can only be left on one that isn't
+                if let Some(other) = self.action_of(binding) {
+                    return Err(format!(
+                        "`{name}` is already bound to `{}`, which needs to be listed too",
+                        other.key()
+                    ));
+                }
+
+                self.bind(action, binding);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The contents of the `[bindings]` table, listing every
+    /// action
+    pub fn to_table(&self) -> String {
+        let mut table = String::new();
+        for action in InputAction::ALL {
+            let bindings: Vec<String> = self
+                .get(action)
+                .iter()
+                .map(|binding| format!("\"{binding}\""))
+                .collect();
+
+            table.push_str(&format!(
+                "{} = [{}]\n",
+                action.key(),
+                bindings.join(", ")
+            ));
+        }
+
+        table
+    }
+}
+
+/// The key that opens the developer console, which is kept
+/// out of [`KEYS`] so that it can't do anything else
+pub const CONSOLE_KEY: Key = Key::Backquote;
+
+/// Every key that can be bound, which is every one tetra knows
+/// but [`CONSOLE_KEY`]
+const KEYS: [Key; 111] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::F16,
+    Key::F17,
+    Key::F18,
+    Key::F19,
+    Key::F20,
+    Key::F21,
+    Key::F22,
+    Key::F23,
+    Key::F24,
+    Key::NumLock,
+    Key::NumPad1,
+    Key::NumPad2,
+    Key::NumPad3,
+    Key::NumPad4,
+    Key::NumPad5,
+    Key::NumPad6,
+    Key::NumPad7,
+    Key::NumPad8,
+    Key::NumPad9,
+    Key::NumPad0,
+    Key::NumPadPlus,
+    Key::NumPadMinus,
+    Key::NumPadMultiply,
+    Key::NumPadDivide,
+    Key::NumPadEnter,
+    Key::LeftCtrl,
+    Key::LeftShift,
+    Key::LeftAlt,
+    Key::RightCtrl,
+    Key::RightShift,
+    Key::RightAlt,
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Backslash,
+    Key::Backspace,
+    Key::CapsLock,
+    Key::Comma,
+    Key::Delete,
+    Key::End,
+    Key::Enter,
+    Key::Equals,
+    Key::Escape,
+    Key::Home,
+    Key::Insert,
+    Key::LeftBracket,
+    Key::Minus,
+    Key::PageDown,
+    Key::PageUp,
+    Key::Pause,
+    Key::Period,
+    Key::PrintScreen,
+    Key::Quote,
+    Key::RightBracket,
+    Key::ScrollLock,
+    Key::Semicolon,
+    Key::Slash,
+    Key::Space,
+    Key::Tab,
+];
+
+const BUTTONS: [GamepadButton; 17] = [
+    GamepadButton::A,
+    GamepadButton::B,
+    GamepadButton::X,
+    GamepadButton::Y,
+    GamepadButton::Up,
+    GamepadButton::Down,
+    GamepadButton::Left,
+    GamepadButton::Right,
+    GamepadButton::LeftShoulder,
+    GamepadButton::LeftTrigger,
+    GamepadButton::LeftStick,
+    GamepadButton::RightShoulder,
+    GamepadButton::RightTrigger,
+    GamepadButton::RightStick,
+    GamepadButton::Start,
+    GamepadButton::Back,
+    GamepadButton::Guide,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(source: &str) -> Result<Bindings, String> {
+        let table = toml::from_str(source).unwrap();
+        let mut bindings = Bindings::default();
+        bindings.read_table(&table)?;
+
+        Ok(bindings)
+    }
+
+    #[test]
+    fn tables_are_written_back_the_same() {
+        let source = "\
+            move_up = [\"Up\", \"Pad Up\"]\n\
+            fire_up = [\"I\"]\n\
+            pause = [\"Pad Start\", \"Pad Back\"]\n";
+        let bindings = read(source).unwrap();
+
+        assert_eq!(
+            bindings.get(InputAction::MoveUp),
+            [
+                Binding::Key(Key::Up),
+                Binding::Button(GamepadButton::Up)
+            ]
+        );
+        assert_eq!(
+            bindings.get(InputAction::Pause),
+            [
+                Binding::Button(GamepadButton::Start),
+                Binding::Button(GamepadButton::Back)
+            ]
+        );
+
+        let table = bindings.to_table();
+        assert!(
+            table.contains("move_up = [\"Up\", \"Pad Up\"]\n")
+        );
+        assert!(table.contains(
+            "pause = [\"Pad Start\", \"Pad Back\"]\n"
+        ));
+        assert_eq!(read(&table).unwrap().to_table(), table);
+    }
+
+    #[test]
+    fn every_binding_reads_back_by_its_name() {
+        let bindings = KEYS
+            .iter()
+            .map(|&key| Binding::Key(key))
+            .chain(BUTTONS.iter().map(|&b| Binding::Button(b)));
+
+        for binding in bindings {
+            assert_eq!(
+                Binding::from_name(&binding.to_string()),
+                Some(binding)
+            );
+        }
+    }
+
+    #[test]
+    fn buttons_need_their_prefix() {
+        assert_eq!(
+            Binding::from_name("A"),
+            Some(Binding::Key(Key::A))
+        );
+        assert_eq!(
+            Binding::from_name("Pad A"),
+            Some(Binding::Button(GamepadButton::A))
+        );
+        assert_eq!(Binding::from_name("Pad Q"), None);
+        assert_eq!(Binding::from_name("Start"), None);
+    }
+
+    #[test]
+    fn the_console_key_cant_be_bound() {
+        assert_eq!(Binding::from_name("Backquote"), None);
+        assert_eq!(
+            read("fire_up = [\"Backquote\"]").unwrap_err(),
+            "unknown key or button `Backquote`"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use tetra::math::Vec2;
+
+use crate::{
+    input::InputFrame, simulation::Simulation, HEIGHT, WIDTH,
+};
+
+/// How close (in pixels) an enemy has to be for the bot to run
+/// away from it
+const DANGER_RADIUS: f32 = 220.0;
+
+/// How far an axis of the bot's heading has to lean before it
+/// presses that direction
+const PRESS_THRESHOLD: f32 = 0.3;
+
+/// Plays the game on its own, for the attract mode of the
+/// title screen and for headless runs.
+///
+/// It shoots at the closest enemy while running away from the
+/// ones nearby and staying close to the center of the arena.
+pub fn play(sim: &Simulation) -> InputFrame {
+    let player = sim.player_manager().player();
+    let position = player.position;
+    let enemies = &sim.enemy_mgr().enemies;
+
+    let fire_angle = enemies
+        .iter()
+        .min_by(|a, b| {
+            position.distance_squared(a.position).total_cmp(
+                &position.distance_squared(b.position),
+            )
+        })
+        .map(|enemy| {
+            player.angle_to_pos(enemy.position).to_degrees()
+        });
+
+    // The pull towards the center grows the further away from
+    // it the bot is
+    let center = Vec2::new(WIDTH as f32, HEIGHT as f32) / 2.0;
+    let mut heading = (center - position) / center.x;
+
+    for enemy in enemies {
+        let away = position - enemy.position;
+        let distance = away.magnitude();
+
+        if distance > 0.0 && distance < DANGER_RADIUS {
+            heading += away / distance
+                * (1.0 - distance / DANGER_RADIUS)
+                * 2.0;
+        }
+    }
+
+    // Only press the keys a player would, so that the bot moves
+    // just like them
+    let press = |axis: f32| {
+        if axis > PRESS_THRESHOLD {
+            1.0
+        } else if axis < -PRESS_THRESHOLD {
+            -1.0
+        } else {
+            0.0
+        }
+    };
+
+    InputFrame {
+        movement: Vec2::new(press(heading.x), press(heading.y)),
+        fire_angle,
+    }
+}
@@ -0,0 +1,418 @@
+use std::path::PathBuf;
+
+use tetra::graphics::scaling::ScalingMode;
+
+use crate::{
+    difficulty::Difficulty, gamestate::SCALING_MODES, VERSION,
+};
+
+/// The options the game was launched with
+#[derive(Debug, Default)]
+pub struct Args {
+    /// The seed for the game's random number generator. A
+    /// random one is picked if not given
+    pub seed: Option<u64>,
+    /// The difficulty to play on instead of the one in the
+    /// settings
+    pub difficulty: Option<Difficulty>,
+    /// The wave to start the run at, counting from zero
+    pub wave: Option<u16>,
+    /// Where to record the inputs of this run
+    pub record: Option<PathBuf>,
+    /// A replay file to play back instead of reading the
+    /// keyboard
+    pub replay: Option<PathBuf>,
+    /// A file describing the waves to play instead of the
+    /// built-in ones
+    pub waves: Option<PathBuf>,
+    /// Whether to open the window fullscreen, instead of
+    /// however the settings say
+    pub fullscreen: Option<bool>,
+    /// How to scale the game, instead of however the settings
+    /// say
+    pub scale_mode: Option<ScalingMode>,
+    /// A config file to use instead of the one in the data
+    /// directory
+    pub config: Option<PathBuf>,
+    /// Whether to simulate the run without a window, with the
+    /// bot playing unless a replay is given
+    pub headless: bool,
+    /// How many ticks a headless run lasts at most
+    pub ticks: Option<u64>,
+    /// Whether the debug overlay can be toggled with F3. It
+    /// always can in debug builds
+    pub debug_overlay: bool,
+    /// Whether the developer console can be opened with the
+    /// backtick key. It always can in debug builds
+    pub console: bool,
+}
+
+/// What the command line asks for
+enum Request {
+    Run(Args),
+    Help,
+    Version,
+}
+
+impl Args {
+    /// Parses the command-line arguments of this process,
+    /// exiting if they're invalid or if `--help` or
+    /// `--version` was given
+    pub fn parse_or_exit() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Request::Run(args)) => args,
+            Ok(Request::Help) => {
+                print!("{USAGE}");
+                std::process::exit(0);
+            }
+            Ok(Request::Version) => {
+                println!("endless-trial {VERSION}");
+                std::process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("error: {err}\n");
+                eprint!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// Whether the arguments ask for a specific run, which
+    /// then skips the title screen
+    pub fn asks_for_run(&self) -> bool {
+        self.seed.is_some()
+            || self.wave.is_some()
+            || self.record.is_some()
+            || self.replay.is_some()
+    }
+
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<Request, String> {
+        let mut parsed = Self::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Request::Help),
+                "-V" | "--version" => {
+                    return Ok(Request::Version)
+                }
+                "--seed" => {
+                    let value =
+                        next_value(&mut args, &arg, "a number")?;
+                    let seed = value.parse().map_err(|_| {
+                        format!("invalid seed `{value}`")
+                    })?;
+
+                    parsed.seed = Some(seed);
+                }
+                "--difficulty" => {
+                    let value = next_value(
+                        &mut args,
+                        &arg,
+                        "a difficulty",
+                    )?;
+                    let difficulty = Difficulty::from_name(
+                        &value,
+                    )
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown difficulty `{value}`, expected one of: {}",
+                            Difficulty::ALL
+                                .map(Difficulty::name)
+                                .join(", ")
+                        )
+                    })?;
+
+                    parsed.difficulty = Some(difficulty);
+                }
+                "--wave" => {
+                    let value =
+                        next_value(&mut args, &arg, "a number")?;
+                    let wave = value
+                        .parse::<u16>()
+                        .ok()
+                        .filter(|&wave| wave > 0)
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid wave `{value}`, expected a number from 1 to {}",
+                                u16::MAX
+                            )
+                        })?;
+
+                    parsed.wave = Some(wave - 1);
+                }
+                "--record" => {
+                    let path =
+                        next_value(&mut args, &arg, "a file")?;
+
+                    parsed.record = Some(path.into());
+                }
+                "--replay" => {
+                    let path =
+                        next_value(&mut args, &arg, "a file")?;
+
+                    parsed.replay = Some(path.into());
+                }
+                "--waves" => {
+                    let path =
+                        next_value(&mut args, &arg, "a file")?;
+
+                    parsed.waves = Some(path.into());
+                }
+                "--windowed" | "--fullscreen" => {
+                    let fullscreen = arg == "--fullscreen";
+                    if parsed.fullscreen == Some(!fullscreen) {
+                        return Err(
+                            "`--windowed` can't be used with `--fullscreen`"
+                                .into(),
+                        );
+                    }
+
+                    parsed.fullscreen = Some(fullscreen);
+                }
+                "--scale-mode" => {
+                    let value = next_value(
+                        &mut args,
+                        &arg,
+                        "a scaling mode",
+                    )?;
+                    let mode = SCALING_MODES
+                        .iter()
+                        .copied()
+                        .find(|mode| {
+                            format!("{mode:?}")
+                                .eq_ignore_ascii_case(&value)
+                        })
+                        .ok_or_else(|| {
+                            format!(
+                                "unknown scaling mode `{value}`, expected one of: {}",
+                                SCALING_MODES
+                                    .map(|mode| format!("{mode:?}"))
+                                    .join(", ")
+                            )
+                        })?;
+
+                    parsed.scale_mode = Some(mode);
+                }
+                "--config" => {
+                    let path =
+                        next_value(&mut args, &arg, "a file")?;
+
+                    parsed.config = Some(path.into());
+                }
+                "--headless" => parsed.headless = true,
+                "--ticks" => {
+                    let value =
+                        next_value(&mut args, &arg, "a number")?;
+                    let ticks = value
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|&ticks| ticks > 0)
+                        .ok_or_else(|| {
+                            format!(
+                                "invalid number of ticks `{value}`"
+                            )
+                        })?;
+
+                    parsed.ticks = Some(ticks);
+                }
+                "--debug-overlay" => parsed.debug_overlay = true,
+                "--console" => parsed.console = true,
+                other => {
+                    return Err(format!(
+                        "unexpected argument `{other}`"
+                    ))
+                }
+            }
+        }
+
+        parsed.validate()?;
+
+        Ok(Request::Run(parsed))
+    }
+
+    /// Rejects options that don't make sense together
+    fn validate(&self) -> Result<(), String> {
+        let conflict = |a: &str, b: &str| {
+            Err(format!("`{a}` can't be used with `{b}`"))
+        };
+
+        if self.replay.is_some() {
+            // A replay brings its own seed and difficulty, always
+            // starts from the first of the built-in waves, and
+            // recording it again would only make a copy of it
+            if self.seed.is_some() {
+                return conflict("--seed", "--replay");
+            }
+            if self.difficulty.is_some() {
+                return conflict("--difficulty", "--replay");
+            }
+            if self.wave.is_some() {
+                return conflict("--wave", "--replay");
+            }
+            if self.record.is_some() {
+                return conflict("--record", "--replay");
+            }
+            if self.waves.is_some() {
+                return conflict("--waves", "--replay");
+            }
+        }
+
+        // Replays don't know which wave they started from
+        if self.wave.is_some() && self.record.is_some() {
+            return conflict("--wave", "--record");
+        }
+
+        if self.headless {
+            if self.ticks.is_none() && self.replay.is_none() {
+                return Err(
+                    "`--headless` needs `--ticks`, unless it plays a replay"
+                        .into(),
+                );
+            }
+
+            let window_options = [
+                ("--windowed", self.fullscreen == Some(false)),
+                ("--fullscreen", self.fullscreen == Some(true)),
+                ("--scale-mode", self.scale_mode.is_some()),
+                ("--config", self.config.is_some()),
+                ("--debug-overlay", self.debug_overlay),
+                ("--console", self.console),
+            ];
+            if let Some((option, _)) =
+                window_options.iter().find(|(_, given)| *given)
+            {
+                return conflict(option, "--headless");
+            }
+        } else if self.ticks.is_some() {
+            return Err(
+                "`--ticks` can only be used with `--headless`"
+                    .into(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The value that comes after `option`
+fn next_value(
+    args: &mut impl Iterator<Item = String>,
+    option: &str,
+    expected: &str,
+) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("`{option}` expects {expected}"))
+}
+
+const USAGE: &str = "\
+Usage: endless-trial [OPTIONS]
+
+Giving --seed, --wave, --record or --replay starts a run right
+away instead of showing the title screen.
+
+Run options:
+  --seed <N>            Play a reproducible run from the given seed
+  --difficulty <NAME>   Play on Easy, Normal, Hard or Nightmare
+  --wave <N>            Start the run at wave N, which keeps it out
+                        of the high scores
+  --record <FILE>       Record the inputs of this run into a replay
+  --replay <FILE>       Play back a recorded replay, which is always
+                        played on the built-in waves
+  --waves <FILE>        Play the waves described in a TOML file
+
+Window options:
+  --windowed            Open in a window, whatever the settings say
+  --fullscreen          Open fullscreen, whatever the settings say
+  --scale-mode <MODE>   Scale the game with Fixed, Stretch, ShowAll,
+                        ShowAllPixelPerfect, Crop or CropPixelPerfect
+  --config <FILE>       Load and save the settings in FILE
+
+Batch simulation:
+  --headless            Simulate the run without a window and print
+                        how it went. The bot plays, unless --replay
+                        is given. The settings aren't read, so runs
+                        are on Normal unless --difficulty is given
+  --ticks <N>           Stop a headless run after N ticks, of which
+                        there are 60 per second
+
+Debugging:
+  --debug-overlay       Allow toggling the debug overlay with F3
+  --console             Allow opening the developer console with `
+
+  -h, --help            Print this message
+  -V, --version         Print the version
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Request, String> {
+        Args::parse(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    fn error_of(args: &[&str]) -> String {
+        match parse(args) {
+            Ok(_) => {
+                panic!("expected {:?} to be rejected", args)
+            }
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn replays_bring_their_own_run() {
+        for option in [
+            ["--seed", "1"],
+            ["--difficulty", "Hard"],
+            ["--wave", "2"],
+            ["--record", "run.etrp"],
+            ["--waves", "waves.toml"],
+        ] {
+            let mut args = vec!["--replay", "run.etrp"];
+            args.extend(option);
+
+            assert_eq!(
+                error_of(&args),
+                format!(
+                    "`{}` can't be used with `--replay`",
+                    option[0]
+                )
+            );
+        }
+
+        assert!(parse(&["--replay", "run.etrp", "--headless"])
+            .is_ok());
+    }
+
+    #[test]
+    fn headless_runs_need_an_end() {
+        assert_eq!(
+            error_of(&["--headless"]),
+            "`--headless` needs `--ticks`, unless it plays a replay"
+        );
+        assert_eq!(
+            error_of(&[
+                "--headless",
+                "--ticks",
+                "60",
+                "--console"
+            ]),
+            "`--console` can't be used with `--headless`"
+        );
+        assert_eq!(
+            error_of(&["--ticks", "60"]),
+            "`--ticks` can only be used with `--headless`"
+        );
+        assert!(parse(&[
+            "--headless",
+            "--ticks",
+            "60",
+            "--waves",
+            "waves.toml"
+        ])
+        .is_ok());
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+/// A virtual clock for the game logic.
+///
+/// Unlike [`std::time::Instant`], game time only moves
+/// forward when the clock is advanced explicitly, which lets
+/// the game be paused, slowed down or fast-forwarded.
+#[derive(Debug, Clone, Copy)]
+pub struct GameClock {
+    /// How much game time has passed since the clock was
+    /// created
+    now: Duration,
+    /// How much game time the last call to `advance` added
+    delta: Duration,
+    /// How fast game time passes relative to the time it's
+    /// advanced by
+    time_scale: f32,
+    paused: bool,
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        Self {
+            now: Duration::ZERO,
+            delta: Duration::ZERO,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+
+    /// The current moment, as the time since the clock was
+    /// created
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// How much game time the last call to `advance` added, in
+    /// seconds
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Moves the clock forward by `elapsed`, scaled by the
+    /// current time scale. Does nothing while paused.
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            // An f32 would round the elapsed time, making one
+            // long advance end up somewhere else than many
+            // short ones
+            elapsed.mul_f64(self.time_scale as f64)
+        };
+
+        self.now += self.delta;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Makes game time pass `time_scale` times as fast as the
+    /// time the clock is advanced by.
+    ///
+    /// # Panics
+    ///
+    /// If `time_scale` is negative, infinite or NaN, since time
+    /// couldn't be advanced by it
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        assert!(
+            time_scale.is_finite() && time_scale >= 0.0,
+            "time scale should be a finite number of at least \
+             zero, got {}",
+            time_scale
+        );
+        self.time_scale = time_scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advances_by_the_elapsed_time() {
+        let mut clock = GameClock::new();
+        clock.advance(Duration::from_millis(250));
+        clock.advance(Duration::from_millis(500));
+
+        assert_eq!(clock.now(), Duration::from_millis(750));
+        assert_eq!(clock.delta_secs(), 0.5);
+    }
+
+    #[test]
+    fn paused_clock_does_not_advance() {
+        let mut clock = GameClock::new();
+        clock.advance(Duration::from_secs(1));
+
+        clock.set_paused(true);
+        clock.advance(Duration::from_secs(5));
+        assert!(clock.is_paused());
+        assert_eq!(clock.now(), Duration::from_secs(1));
+        assert_eq!(clock.delta_secs(), 0.0);
+
+        clock.set_paused(false);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn scaled_time_moves_at_the_set_factor() {
+        let mut clock = GameClock::new();
+
+        clock.set_time_scale(2.0);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(2));
+        assert_eq!(clock.delta_secs(), 2.0);
+
+        clock.set_time_scale(0.25);
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(clock.now(), Duration::from_secs(3));
+        assert_eq!(clock.delta_secs(), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "time scale")]
+    fn negative_time_scale_is_rejected() {
+        GameClock::new().set_time_scale(-1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "time scale")]
+    fn nan_time_scale_is_rejected() {
+        GameClock::new().set_time_scale(f32::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "time scale")]
+    fn infinite_time_scale_is_rejected() {
+        GameClock::new().set_time_scale(f32::INFINITY);
+    }
+}
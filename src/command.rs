@@ -0,0 +1,347 @@
+//! The commands of the developer console, which change a run
+//! while it's being played.
+
+use crate::{
+    humanoid::HumanoidType, powerup::PowerUpKind,
+    simulation::Simulation, waves::ENEMY_NAMES,
+};
+
+/// The most enemies a single `spawn` can bring in
+const MAX_SPAWN_COUNT: u32 = 100;
+/// The most hearts the console can give, as many as the health
+/// bar fits across the screen
+const MAX_HEARTS: u8 = 40;
+
+/// Every command, along with how it's used
+pub const COMMANDS: [(&str, &str); 10] = [
+    ("help", "help"),
+    ("spawn", "spawn <enemy> [count]"),
+    ("wave", "wave <number>"),
+    ("god", "god"),
+    ("give", "give <power-up>"),
+    ("hearts", "hearts <count>"),
+    ("timescale", "timescale <factor>"),
+    ("kill_all", "kill_all"),
+    ("seed", "seed"),
+    ("clear", "clear"),
+];
+
+pub enum Command {
+    Help,
+    /// Spawns enemies of a kind, which is also given by name
+    Spawn(HumanoidType, &'static str, u32),
+    /// Jumps to a wave, counting from zero
+    Wave(u16),
+    God,
+    Give(PowerUpKind),
+    Hearts(u8),
+    TimeScale(f32),
+    KillAll,
+    Seed,
+    /// Empties the console
+    Clear,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+
+        let usage = COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+            .map(|&(_, usage)| usage)
+            .ok_or_else(|| {
+                format!("unknown command `{name}`, try `help`")
+            })?;
+        let (required, optional) = usage
+            .split_whitespace()
+            .skip(1)
+            .fold((0, 0), |(required, optional), arg| {
+                if arg.starts_with('[') {
+                    (required, optional + 1)
+                } else {
+                    (required + 1, optional)
+                }
+            });
+        if args.len() < required
+            || args.len() > required + optional
+        {
+            return Err(format!("usage: {usage}"));
+        }
+
+        let command = match name {
+            "help" => Command::Help,
+            "spawn" => {
+                let &(enemy, kind) = ENEMY_NAMES
+                    .iter()
+                    .find(|(enemy, _)| *enemy == args[0])
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown enemy `{}`, expected one of: {}",
+                            args[0],
+                            argument_names(name).join(", ")
+                        )
+                    })?;
+                let count = match args.get(1) {
+                    Some(count) => parse_number(
+                        count,
+                        1..=MAX_SPAWN_COUNT,
+                    )?,
+                    None => 1,
+                };
+
+                Command::Spawn(kind, enemy, count)
+            }
+            "wave" => {
+                let wave: u16 = parse_number(args[0], 1..=u16::MAX)?;
+                Command::Wave(wave - 1)
+            }
+            "god" => Command::God,
+            "give" => PowerUpKind::ALL
+                .iter()
+                .find(|kind| kind.name() == args[0])
+                .map(|&kind| Command::Give(kind))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown power-up `{}`, expected one of: {}",
+                        args[0],
+                        argument_names(name).join(", ")
+                    )
+                })?,
+            "hearts" => {
+                Command::Hearts(parse_number(args[0], 1..=MAX_HEARTS)?)
+            }
+            "timescale" => Command::TimeScale(parse_number(
+                args[0],
+                0.05..=10.0,
+            )?),
+            "kill_all" => Command::KillAll,
+            "seed" => Command::Seed,
+            "clear" => Command::Clear,
+            _ => unreachable!("every command should be parsed"),
+        };
+
+        Ok(command)
+    }
+
+    /// Whether running the command makes the run play out
+    /// differently than it would have
+    pub fn changes_run(&self) -> bool {
+        !matches!(
+            self,
+            Command::Help | Command::Seed | Command::Clear
+        )
+    }
+
+    /// Runs the command, returning what the console should
+    /// print back. The run is left as it was if it fails
+    pub fn run(
+        self,
+        sim: &mut Simulation,
+    ) -> Result<String, String> {
+        let output = match self {
+            Command::Help => COMMANDS
+                .iter()
+                .map(|(_, usage)| *usage)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Command::Spawn(kind, name, count) => {
+                sim.spawn_enemies_of(kind, count);
+                format!("Spawned {name} x{count}")
+            }
+            Command::Wave(wave) => {
+                if !sim.has_wave(wave) {
+                    return Err(format!(
+                        "there are only {} waves",
+                        sim.waves().list.len()
+                    ));
+                }
+
+                sim.jump_to_wave(wave);
+                format!("Jumped to wave {}", wave + 1)
+            }
+            Command::God => {
+                let player = sim.player_mut();
+                player.is_invincible = !player.is_invincible;
+
+                if player.is_invincible {
+                    "God mode on".to_owned()
+                } else {
+                    "God mode off".to_owned()
+                }
+            }
+            Command::Give(PowerUpKind::AdditionalHeart) => {
+                let player = sim.player_mut();
+                if player.hearts < MAX_HEARTS {
+                    player.hearts += 1;
+                }
+                format!(
+                    "The player has {} hearts",
+                    player.hearts
+                )
+            }
+            Command::Give(kind) => {
+                let clock = *sim.clock();
+                sim.player_mut()
+                    .power_ups
+                    .activate_power_up(kind, &clock);
+                format!("Activated {}", kind.name())
+            }
+            Command::Hearts(hearts) => {
+                sim.player_mut().hearts = hearts;
+                format!("The player has {hearts} hearts")
+            }
+            Command::TimeScale(time_scale) => {
+                sim.clock_mut().set_time_scale(time_scale);
+                format!("Time runs {time_scale}x as fast")
+            }
+            Command::KillAll => {
+                format!(
+                    "Killed {} enemies",
+                    sim.kill_all_enemies()
+                )
+            }
+            Command::Seed => {
+                format!("The seed of this run is {}", sim.seed())
+            }
+            // The console empties itself
+            Command::Clear => String::new(),
+        };
+
+        Ok(output)
+    }
+}
+
+/// The values the only argument of a command can take, if
+/// they're known in advance
+pub fn argument_names(command: &str) -> Vec<&'static str> {
+    match command {
+        "spawn" => {
+            ENEMY_NAMES.iter().map(|(name, _)| *name).collect()
+        }
+        "give" => PowerUpKind::ALL
+            .iter()
+            .map(|kind| kind.name())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_number<T>(
+    value: &str,
+    range: std::ops::RangeInclusive<T>,
+) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    value
+        .parse()
+        .ok()
+        .filter(|number| range.contains(number))
+        .ok_or_else(|| {
+            format!(
+                "expected a number from {} to {}, got `{value}`",
+                range.start(),
+                range.end()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_of(line: &str) -> String {
+        match Command::parse(line) {
+            Ok(_) => panic!("`{}` should be rejected", line),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert!(matches!(
+            Command::parse("spawn boss 3"),
+            Ok(Command::Spawn(_, "boss", 3))
+        ));
+        assert!(matches!(
+            Command::parse("spawn basic"),
+            Ok(Command::Spawn(_, "basic", 1))
+        ));
+        assert!(matches!(
+            Command::parse("wave 6"),
+            Ok(Command::Wave(5))
+        ));
+        assert!(matches!(
+            Command::parse("give triple_shooting"),
+            Ok(Command::Give(PowerUpKind::TripleShooting))
+        ));
+        assert!(matches!(
+            Command::parse("  timescale   0.25 "),
+            Ok(Command::TimeScale(scale)) if scale == 0.25
+        ));
+        assert!(matches!(
+            Command::parse("hearts 40"),
+            Ok(Command::Hearts(40))
+        ));
+    }
+
+    #[test]
+    fn wrong_argument_counts_show_the_usage() {
+        assert_eq!(
+            error_of("spawn"),
+            "usage: spawn <enemy> [count]"
+        );
+        assert_eq!(
+            error_of("spawn boss 3 4"),
+            "usage: spawn <enemy> [count]"
+        );
+        assert_eq!(error_of("wave"), "usage: wave <number>");
+        assert_eq!(error_of("god mode"), "usage: god");
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert_eq!(
+            error_of("fly"),
+            "unknown command `fly`, try `help`"
+        );
+        assert_eq!(
+            error_of("spawn dragon"),
+            "unknown enemy `dragon`, expected one of: \
+             basic, stronger, badass, boss"
+        );
+        assert!(error_of("give wings")
+            .starts_with("unknown power-up `wings`"));
+    }
+
+    #[test]
+    fn numbers_out_of_range_are_rejected() {
+        assert_eq!(
+            error_of("hearts 255"),
+            "expected a number from 1 to 40, got `255`"
+        );
+        assert_eq!(
+            error_of("hearts 0"),
+            "expected a number from 1 to 40, got `0`"
+        );
+        assert_eq!(
+            error_of("spawn basic 500"),
+            "expected a number from 1 to 100, got `500`"
+        );
+        assert_eq!(
+            error_of("wave 0"),
+            "expected a number from 1 to 65535, got `0`"
+        );
+        assert_eq!(
+            error_of("timescale 20"),
+            "expected a number from 0.05 to 10, got `20`"
+        );
+        assert_eq!(
+            error_of("timescale fast"),
+            "expected a number from 0.05 to 10, got `fast`"
+        );
+    }
+}
@@ -0,0 +1,620 @@
+//! The player's settings, kept across sessions.
+//!
+//! They're saved as TOML in the
+//! [data directory](crate::paths::data_dir), or wherever
+//! `--config` points at:
+//!
+//! ```toml
+//! version = 2
+//!
+//! [window]
+//! width = 800
+//! height = 800
+//! fullscreen = false
+//! vsync = true
+//! scaling = "ShowAll"
+//!
+//! [gameplay]
+//! difficulty = "Normal"
+//!
+//! [audio]
+//! master_volume = 100
+//! music_volume = 70
+//! effects_volume = 100
+//! muted = false
+//!
+//! [controls]
+//! aim = "Keys"
+//!
+//! [bindings]
+//! move_up = ["W", "Pad Up"]
+//! pause = ["Escape", "P", "Pad Start"]
+//! ```
+//!
+//! Every action of [`InputAction`] has its own list of
+//! bindings, named by [`InputAction::key`]. A key can only be
+//! taken from another action if that action is listed too.
+//!
+//! Version 1 files have no version, window or gameplay
+//! settings, and their volumes go from 0.0 to 1.0 instead of
+//! being percentages.
+//!
+//! Missing settings keep their defaults, so that the file
+//! only needs to hold what the player changed.
+//!
+//! [`InputAction`]: crate::bindings::InputAction
+//! [`InputAction::key`]: crate::bindings::InputAction::key
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use tetra::graphics::scaling::ScalingMode;
+
+use crate::{
+    difficulty::Difficulty,
+    gamestate::SCALING_MODES,
+    input::{AimMode, ControlSettings},
+    paths,
+    sound::AudioSettings,
+    HEIGHT, WIDTH,
+};
+
+const FILE_NAME: &str = "config.toml";
+const VERSION: i64 = 2;
+
+/// The smallest and largest size a side of the window can be
+/// set to in the file
+const WINDOW_SIDE_RANGE: std::ops::RangeInclusive<i64> =
+    200..=8192;
+
+/// How the window is opened and the game drawn into it
+#[derive(Debug, Clone, Copy)]
+pub struct WindowSettings {
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub scaling: ScalingMode,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            fullscreen: false,
+            vsync: true,
+            scaling: ScalingMode::ShowAll,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Config {
+    pub window: WindowSettings,
+    /// The difficulty new runs are played on
+    pub difficulty: Difficulty,
+    pub audio: AudioSettings,
+    pub controls: ControlSettings,
+    /// Where the config is saved, if we know where to keep it
+    path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the data
+    /// directory if not given, falling back to the defaults if
+    /// there's none yet or it can't be read. It's then saved
+    /// back to the same place, unless it was written by a newer
+    /// version of the game
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let Some(path) = path.or_else(|| {
+            paths::data_dir().map(|dir| dir.join(FILE_NAME))
+        }) else {
+            eprintln!(
+                "[LOG] No data directory found, settings won't be saved"
+            );
+            return Self::default();
+        };
+
+        let Some(mut config) = Self::read(&path) else {
+            return Self::default();
+        };
+        config.path = Some(path);
+
+        config
+    }
+
+    /// Returns `None` if the file is from a newer version of
+    /// the game, which may have settings we don't know of. It's
+    /// then left as it is, and nothing is saved over it
+    fn read(path: &Path) -> Option<Self> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err)
+                if err.kind() == io::ErrorKind::NotFound =>
+            {
+                return Some(Self::default())
+            }
+            Err(err) => {
+                eprintln!(
+                    "[LOG] Failed to read {}: {err}, using the default settings",
+                    path.display()
+                );
+                return Some(Self::default());
+            }
+        };
+
+        if let Some(version) = newer_version(&source) {
+            eprintln!(
+                "[LOG] {} is from a newer version of the game (version {version}), using the default settings without saving them",
+                path.display()
+            );
+            return None;
+        }
+
+        Some(match parse(&source) {
+            Ok(config) => config,
+            Err(err) => {
+                let backup = paths::move_aside(path);
+                eprintln!(
+                    "[LOG] Failed to read {}: {err}, moved it to {} and using the default settings",
+                    path.display(),
+                    backup.display()
+                );
+
+                Self::default()
+            }
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if let Err(err) =
+            paths::write_atomically(path, &self.to_toml())
+        {
+            eprintln!(
+                "[LOG] Failed to save the settings: {err}"
+            );
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        let window = &self.window;
+        let audio = &self.audio;
+        let percent = |volume: f32| (volume * 100.0).round();
+
+        let mut toml = format!(
+            "version = {VERSION}\n\
+             \n\
+             [window]\n\
+             width = {}\n\
+             height = {}\n\
+             fullscreen = {}\n\
+             vsync = {}\n\
+             scaling = \"{:?}\"\n\
+             \n\
+             [gameplay]\n\
+             difficulty = \"{}\"\n\
+             \n\
+             [audio]\n\
+             master_volume = {}\n\
+             music_volume = {}\n\
+             effects_volume = {}\n\
+             muted = {}\n\
+             \n\
+             [controls]\n\
+             aim = \"{}\"\n",
+            window.width,
+            window.height,
+            window.fullscreen,
+            window.vsync,
+            window.scaling,
+            self.difficulty.name(),
+            percent(audio.master_volume),
+            percent(audio.music_volume),
+            percent(audio.effects_volume),
+            audio.muted,
+            self.controls.aim.name()
+        );
+
+        toml.push_str("\n[bindings]\n");
+        toml.push_str(&self.controls.bindings.to_table());
+
+        toml
+    }
+}
+
+/// The settings as written in the file, before they're
+/// checked. Everything is optional
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    version: Option<i64>,
+    window: RawWindow,
+    gameplay: RawGameplay,
+    audio: RawAudio,
+    controls: RawControls,
+    /// The bindings of each action, by
+    /// [`InputAction::key`](crate::bindings::InputAction::key)
+    bindings: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawWindow {
+    width: Option<i64>,
+    height: Option<i64>,
+    fullscreen: Option<bool>,
+    vsync: Option<bool>,
+    scaling: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawGameplay {
+    difficulty: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawAudio {
+    master_volume: Option<f64>,
+    music_volume: Option<f64>,
+    effects_volume: Option<f64>,
+    muted: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RawControls {
+    aim: Option<String>,
+}
+
+/// Only the version of a file, which can be read whatever else
+/// the file holds
+#[derive(Deserialize)]
+struct RawVersion {
+    version: Option<i64>,
+}
+
+/// The version of the file, if it's newer than the ones we can
+/// read
+fn newer_version(source: &str) -> Option<i64> {
+    let raw: RawVersion = toml::from_str(source).ok()?;
+
+    raw.version.filter(|&version| version > VERSION)
+}
+
+fn parse(source: &str) -> Result<Config, String> {
+    let raw: RawConfig =
+        toml::from_str(source).map_err(|err| err.to_string())?;
+
+    let version = raw.version.unwrap_or(1);
+    if !(1..=VERSION).contains(&version) {
+        return Err(format!(
+            "version {version} is not one we can read, expected 1 to {VERSION}"
+        ));
+    }
+
+    let mut config = Config::default();
+    if version >= 2 {
+        config.window = check_window(&raw.window)?;
+        if let Some(name) = &raw.gameplay.difficulty {
+            config.difficulty = parse_named(
+                name,
+                "difficulty",
+                &Difficulty::ALL,
+                |difficulty| difficulty.name().to_owned(),
+            )?;
+        }
+    }
+    config.audio = check_audio(&raw.audio, version)?;
+    if let Some(name) = &raw.controls.aim {
+        config.controls.aim = parse_named(
+            name,
+            "aim mode",
+            &AimMode::ALL,
+            |mode| mode.name().to_owned(),
+        )?;
+    }
+    config.controls.bindings.read_table(&raw.bindings)?;
+
+    Ok(config)
+}
+
+/// Reads one of `all` by the name `name` gives it, ignoring
+/// case
+fn parse_named<T: Copy>(
+    value: &str,
+    what: &str,
+    all: &[T],
+    name: impl Fn(T) -> String,
+) -> Result<T, String> {
+    all.iter()
+        .copied()
+        .find(|&option| name(option).eq_ignore_ascii_case(value))
+        .ok_or_else(|| {
+            format!(
+                "unknown {what} `{value}`, expected one of: {}",
+                all.iter()
+                    .map(|&option| name(option))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+fn check_window(
+    raw: &RawWindow,
+) -> Result<WindowSettings, String> {
+    let mut window = WindowSettings::default();
+    for (key, value, side) in [
+        ("width", raw.width, &mut window.width),
+        ("height", raw.height, &mut window.height),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+
+        if !WINDOW_SIDE_RANGE.contains(&value) {
+            return Err(format!(
+                "`{key}` should be from {} to {}",
+                WINDOW_SIDE_RANGE.start(),
+                WINDOW_SIDE_RANGE.end()
+            ));
+        }
+        *side = value as i32;
+    }
+
+    if let Some(fullscreen) = raw.fullscreen {
+        window.fullscreen = fullscreen;
+    }
+    if let Some(vsync) = raw.vsync {
+        window.vsync = vsync;
+    }
+    if let Some(name) = &raw.scaling {
+        window.scaling = parse_named(
+            name,
+            "scaling mode",
+            &SCALING_MODES,
+            |mode| format!("{mode:?}"),
+        )?;
+    }
+
+    Ok(window)
+}
+
+fn check_audio(
+    raw: &RawAudio,
+    version: i64,
+) -> Result<AudioSettings, String> {
+    let mut audio = AudioSettings::default();
+    // Volumes were saved from 0 to 1 before they became
+    // percentages
+    let max = if version >= 2 { 100.0 } else { 1.0 };
+
+    for (key, value, volume) in [
+        (
+            "master_volume",
+            raw.master_volume,
+            &mut audio.master_volume,
+        ),
+        (
+            "music_volume",
+            raw.music_volume,
+            &mut audio.music_volume,
+        ),
+        (
+            "effects_volume",
+            raw.effects_volume,
+            &mut audio.effects_volume,
+        ),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+
+        if !(0.0..=max).contains(&value) {
+            return Err(format!(
+                "`{key}` should be between 0 and {max}"
+            ));
+        }
+        *volume = (value / max) as f32;
+    }
+
+    if let Some(muted) = raw.muted {
+        audio.muted = muted;
+    }
+
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use tetra::input::Key;
+
+    use super::*;
+    use crate::bindings::{Binding, InputAction};
+    use crate::paths::TempDir;
+
+    const VERSION_1: &str = "\
+        [audio]\n\
+        master_volume = 0.5\n\
+        music_volume = 0.25\n\
+        muted = true\n\
+        \n\
+        [controls]\n\
+        aim = \"Mouse\"\n";
+
+    const VERSION_2: &str = "\
+        version = 2\n\
+        \n\
+        [window]\n\
+        width = 1024\n\
+        fullscreen = true\n\
+        scaling = \"Crop\"\n\
+        \n\
+        [gameplay]\n\
+        difficulty = \"hard\"\n\
+        \n\
+        [audio]\n\
+        master_volume = 50\n\
+        effects_volume = 0\n\
+        \n\
+        [bindings]\n\
+        move_up = [\"Up\", \"Pad Up\"]\n\
+        fire_up = [\"I\"]\n";
+
+    fn error_of(source: &str) -> String {
+        match parse(source) {
+            Ok(_) => {
+                panic!("expected {:?} to be rejected", source)
+            }
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn reads_version_1_files() {
+        let config = parse(VERSION_1).unwrap();
+
+        assert_eq!(config.audio.master_volume, 0.5);
+        assert_eq!(config.audio.music_volume, 0.25);
+        assert_eq!(config.audio.effects_volume, 1.0);
+        assert!(config.audio.muted);
+        assert_eq!(config.controls.aim, AimMode::Mouse);
+        assert_eq!(config.difficulty, Difficulty::Normal);
+        assert_eq!(config.window.width, WIDTH);
+    }
+
+    #[test]
+    fn reads_version_2_files() {
+        let config = parse(VERSION_2).unwrap();
+
+        assert_eq!(config.window.width, 1024);
+        assert_eq!(config.window.height, HEIGHT);
+        assert!(config.window.fullscreen);
+        assert!(config.window.vsync);
+        assert_eq!(config.window.scaling, ScalingMode::Crop);
+        assert_eq!(config.difficulty, Difficulty::Hard);
+        assert_eq!(config.audio.master_volume, 0.5);
+        assert_eq!(config.audio.music_volume, 0.7);
+        assert_eq!(config.audio.effects_volume, 0.0);
+
+        let bindings = &config.controls.bindings;
+        assert_eq!(
+            bindings.action_of(Binding::Key(Key::Up)),
+            Some(InputAction::MoveUp)
+        );
+        assert_eq!(
+            bindings.action_of(Binding::Key(Key::W)),
+            None
+        );
+        assert_eq!(
+            bindings.get(InputAction::FireUp),
+            [Binding::Key(Key::I)]
+        );
+        // Actions that aren't listed keep their defaults
+        assert_eq!(
+            bindings.action_of(Binding::Key(Key::S)),
+            Some(InputAction::MoveDown)
+        );
+    }
+
+    #[test]
+    fn volumes_follow_the_version() {
+        assert_eq!(
+            error_of("[audio]\nmaster_volume = 50"),
+            "`master_volume` should be between 0 and 1"
+        );
+        assert_eq!(
+            error_of("version = 2\n[audio]\nmusic_volume = 150"),
+            "`music_volume` should be between 0 and 100"
+        );
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        let err = error_of("[window]\nwidht = 900");
+        assert!(
+            err.contains("unknown field `widht`"),
+            "{}",
+            err
+        );
+
+        assert_eq!(
+            error_of("version = 2\n[gameplay]\ndifficulty = \"Easiest\""),
+            "unknown difficulty `Easiest`, expected one of: Easy, Normal, Hard, Nightmare"
+        );
+    }
+
+    #[test]
+    fn saved_settings_read_back_the_same() {
+        for source in ["", VERSION_1, VERSION_2] {
+            let saved = parse(source).unwrap().to_toml();
+            let reread = parse(&saved).unwrap();
+
+            assert_eq!(reread.to_toml(), saved);
+        }
+    }
+
+    #[test]
+    fn keys_can_only_move_between_listed_actions() {
+        assert_eq!(
+            error_of("[bindings]\nmove_up = [\"Up\"]"),
+            "`Up` is already bound to `fire_up`, which needs to be listed too"
+        );
+        assert_eq!(
+            error_of("[bindings]\nmove_up = [\"W\"]\nfire_up = [\"W\"]"),
+            "`W` is bound twice"
+        );
+        assert!(parse(
+            "[bindings]\nfire_up = []\nmove_up = [\"Up\"]"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn unreadable_files_are_moved_aside() {
+        let dir = TempDir::new("config-unreadable");
+        let path = dir.file(FILE_NAME, "version = 0\n");
+
+        let config = Config::read(&path).unwrap();
+        let backup = dir.join("config.toml.bak");
+
+        assert_eq!(
+            config.to_toml(),
+            Config::default().to_toml()
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(&backup).unwrap(),
+            "version = 0\n"
+        );
+    }
+
+    #[test]
+    fn newer_files_are_left_alone() {
+        let dir = TempDir::new("config-newer");
+        let source =
+            "version = 3\n\n[gameplay]\nnew_setting = 1\n";
+        let path = dir.file(FILE_NAME, source);
+
+        let config = Config::load(Some(path.clone()));
+        assert_eq!(
+            config.to_toml(),
+            Config::default().to_toml()
+        );
+
+        config.save();
+        assert_eq!(fs::read_to_string(&path).unwrap(), source);
+        assert!(!dir.join("config.toml.bak").exists());
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+
+use tetra::{
+    graphics::text::Text,
+    input::{self, Key},
+    math::Vec2,
+    window, Context,
+};
+
+use crate::{
+    command::{argument_names, COMMANDS},
+    panel::Panel,
+    scene::Shared,
+    WIDTH,
+};
+
+/// How many lines of output fit in the console
+const VISIBLE_LINES: usize = 9;
+/// How many lines of output are kept around at most
+const MAX_LINES: usize = 100;
+const LINE_SPACING: f32 = 26.0;
+const PADDING: f32 = 8.0;
+
+/// A drop-down console at the top of the screen, which commands
+/// are typed into
+pub struct Console {
+    panel: Panel,
+    is_open: bool,
+    /// What's been typed so far
+    input: String,
+    history: History,
+    /// What the console printed, from the oldest to the newest
+    lines: VecDeque<String>,
+    output_text: Text,
+    input_text: Text,
+    /// Set whenever the texts don't match what they show
+    is_dirty: bool,
+}
+
+impl Console {
+    pub fn new(shared: &Shared) -> Self {
+        Self {
+            panel: shared.panel.clone(),
+            is_open: false,
+            input: String::new(),
+            history: History::default(),
+            lines: VecDeque::new(),
+            output_text: Text::new(
+                "",
+                shared.fonts.small.clone(),
+            ),
+            input_text: Text::new(
+                "",
+                shared.fonts.small.clone(),
+            ),
+            is_dirty: true,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Opens or closes the console. Held keys repeat while it's
+    /// open, so that text can be erased by holding Backspace
+    pub fn toggle(&mut self, ctx: &mut Context) {
+        self.is_open = !self.is_open;
+        window::set_key_repeat_enabled(ctx, self.is_open);
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.lines.len() == MAX_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line.to_owned());
+        }
+
+        self.is_dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.is_dirty = true;
+    }
+
+    /// Handles typing into the console, returning a line once
+    /// it's entered
+    pub fn update(&mut self, ctx: &Context) -> Option<String> {
+        // The key that opens the console shouldn't type itself
+        if let Some(text) = input::get_text_input(ctx) {
+            let typed =
+                text.chars().filter(|&c| c != '`' && c != '~');
+            self.input.extend(typed);
+            self.is_dirty = true;
+        }
+
+        if input::is_key_pressed(ctx, Key::Backspace) {
+            self.input.pop();
+            self.is_dirty = true;
+        }
+        if input::is_key_pressed(ctx, Key::Up) {
+            self.input = self.history.browse(&self.input, true);
+            self.is_dirty = true;
+        }
+        if input::is_key_pressed(ctx, Key::Down) {
+            self.input = self.history.browse(&self.input, false);
+            self.is_dirty = true;
+        }
+        if input::is_key_pressed(ctx, Key::Tab) {
+            if let Some((completed, options)) =
+                complete(&self.input)
+            {
+                if options.len() > 1 {
+                    self.print(&options.join("  "));
+                }
+                self.input = completed;
+                self.is_dirty = true;
+            }
+        }
+
+        if !input::is_key_pressed(ctx, Key::Enter) {
+            return None;
+        }
+
+        let line = std::mem::take(&mut self.input);
+        self.is_dirty = true;
+
+        let line = line.trim();
+        self.history.enter(line);
+        if line.is_empty() {
+            return None;
+        }
+
+        self.print(&format!("> {line}"));
+
+        Some(line.to_owned())
+    }
+
+    /// Draws the console across the top of the screen, if it's
+    /// open
+    pub fn draw(&mut self, ctx: &mut Context) {
+        if !self.is_open {
+            return;
+        }
+
+        if self.is_dirty {
+            let shown =
+                self.lines.len().saturating_sub(VISIBLE_LINES);
+            let output: Vec<&str> = self
+                .lines
+                .iter()
+                .skip(shown)
+                .map(String::as_str)
+                .collect();
+
+            self.output_text.set_content(output.join("\n"));
+            self.input_text
+                .set_content(format!("> {}_", self.input));
+            self.is_dirty = false;
+        }
+
+        let height = (VISIBLE_LINES + 1) as f32 * LINE_SPACING
+            + 2.0 * PADDING;
+        self.panel.sprite.draw_nine_slice(
+            ctx,
+            &self.panel.config,
+            WIDTH as f32,
+            height,
+            Vec2::zero(),
+        );
+
+        // The output is kept right above the input line
+        let output_height = self
+            .output_text
+            .get_bounds(ctx)
+            .map_or(0.0, |bounds| bounds.height);
+        let input_position =
+            Vec2::new(PADDING, height - PADDING - LINE_SPACING);
+
+        self.output_text.draw(
+            ctx,
+            input_position - Vec2::new(0.0, output_height + 4.0),
+        );
+        self.input_text.draw(ctx, input_position);
+    }
+}
+
+/// Every line entered into the console, which can be browsed
+/// with the arrow keys
+#[derive(Default)]
+struct History {
+    /// From the oldest to the newest
+    entries: Vec<String>,
+    /// The entry being shown in place of the input, if any
+    browsing: Option<usize>,
+    /// What was typed before going through the history
+    draft: String,
+}
+
+impl History {
+    /// Stops browsing, and remembers `line` unless it's empty
+    /// or a repeat of the last one
+    fn enter(&mut self, line: &str) {
+        self.browsing = None;
+
+        if !line.is_empty()
+            && self.entries.last().map(String::as_str)
+                != Some(line)
+        {
+            self.entries.push(line.to_owned());
+        }
+    }
+
+    /// What replaces `input`: an older entry, or a newer one and
+    /// eventually what was being typed
+    fn browse(&mut self, input: &str, older: bool) -> String {
+        // There's nothing newer than the input itself
+        if self.entries.is_empty()
+            || (self.browsing.is_none() && !older)
+        {
+            return input.to_owned();
+        }
+
+        let newest = self.entries.len() - 1;
+        self.browsing = match (self.browsing, older) {
+            (None, true) => {
+                self.draft = input.to_owned();
+                Some(newest)
+            }
+            (Some(entry), true) => Some(entry.saturating_sub(1)),
+            (Some(entry), false) if entry < newest => {
+                Some(entry + 1)
+            }
+            (_, false) => None,
+        };
+
+        match self.browsing {
+            Some(entry) => self.entries[entry].clone(),
+            None => self.draft.clone(),
+        }
+    }
+}
+
+/// Completes the word being typed as far as it's unambiguous.
+/// Returns the completed input along with every option the
+/// word could still become, or `None` if there are none
+fn complete(input: &str) -> Option<(String, Vec<&'static str>)> {
+    let word_start =
+        input.rfind(' ').map_or(0, |space| space + 1);
+    let (head, word) = input.split_at(word_start);
+
+    let mut previous_words = head.split_whitespace();
+    let options =
+        match (previous_words.next(), previous_words.next()) {
+            (None, _) => {
+                COMMANDS.iter().map(|(name, _)| *name).collect()
+            }
+            (Some(command), None) => argument_names(command),
+            _ => Vec::new(),
+        };
+    let options: Vec<&str> = options
+        .into_iter()
+        .filter(|option| option.starts_with(word))
+        .collect();
+
+    let completed = match options.as_slice() {
+        [] => return None,
+        [only] => format!("{head}{only} "),
+        [first, rest @ ..] => {
+            let common =
+                rest.iter().fold(*first, |common, option| {
+                    let length = common
+                        .bytes()
+                        .zip(option.bytes())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    &common[..length]
+                });
+
+            format!("{head}{common}")
+        }
+    };
+
+    Some((completed, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unambiguous_words_are_completed() {
+        assert_eq!(
+            complete("ti"),
+            Some(("timescale ".to_owned(), vec!["timescale"]))
+        );
+        assert_eq!(
+            complete("spawn bo"),
+            Some(("spawn boss ".to_owned(), vec!["boss"]))
+        );
+        assert_eq!(
+            complete("give tr"),
+            Some((
+                "give triple_shooting ".to_owned(),
+                vec!["triple_shooting"]
+            ))
+        );
+    }
+
+    #[test]
+    fn ambiguous_words_are_completed_as_far_as_they_agree() {
+        assert_eq!(
+            complete("spawn b"),
+            Some((
+                "spawn b".to_owned(),
+                vec!["basic", "badass", "boss"]
+            ))
+        );
+        assert_eq!(
+            complete("s"),
+            Some(("s".to_owned(), vec!["spawn", "seed"]))
+        );
+        assert_eq!(
+            complete("k"),
+            Some(("kill_all ".to_owned(), vec!["kill_all"]))
+        );
+    }
+
+    #[test]
+    fn words_without_options_are_left_alone() {
+        assert_eq!(complete("fly"), None);
+        assert_eq!(complete("spawn dragon"), None);
+        assert_eq!(complete("wave 1"), None);
+        assert_eq!(complete("spawn boss 3"), None);
+    }
+
+    #[test]
+    fn history_is_browsed_both_ways() {
+        let mut history = History::default();
+        history.enter("god");
+        history.enter("wave 6");
+        history.enter("wave 6");
+        history.enter("");
+
+        assert_eq!(history.browse("spa", true), "wave 6");
+        assert_eq!(history.browse("wave 6", true), "god");
+        // The oldest entry stays put
+        assert_eq!(history.browse("god", true), "god");
+        assert_eq!(history.browse("god", false), "wave 6");
+        // Past the newest entry, the draft comes back
+        assert_eq!(history.browse("wave 6", false), "spa");
+        assert_eq!(history.browse("spa", false), "spa");
+    }
+
+    #[test]
+    fn entering_a_line_stops_browsing() {
+        let mut history = History::default();
+        history.enter("god");
+        history.enter("seed");
+
+        assert_eq!(history.browse("", true), "seed");
+        assert_eq!(history.browse("seed", true), "god");
+        history.enter("god");
+
+        assert_eq!(history.browse("", true), "god");
+    }
+
+    #[test]
+    fn empty_history_keeps_the_input() {
+        let mut history = History::default();
+
+        assert_eq!(history.browse("clear", true), "clear");
+        assert_eq!(history.browse("clear", false), "clear");
+    }
+}
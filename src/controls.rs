@@ -0,0 +1,226 @@
+use tetra::{
+    graphics::text::Text,
+    input::{self, Key},
+    math::Vec2,
+    Context,
+};
+
+use crate::{
+    bindings::{Binding, Bindings, InputAction, CONSOLE_KEY},
+    menu::Menu,
+    scene::{Scene, Shared, Transition},
+    HEIGHT, WIDTH,
+};
+
+/// How far below the menu the hint is drawn
+const HINT_SPACING: f32 = 12.0;
+
+const DEFAULT_HINT: &str =
+    "Enter: add a binding   Backspace: clear   Esc: back";
+const LISTENING_HINT: &str =
+    "Press a key or gamepad button, Esc to cancel";
+
+/// The entries of the controls screen
+#[derive(Clone, Copy, PartialEq)]
+enum ControlsEntry {
+    Action(InputAction),
+    Reset,
+    Back,
+}
+
+fn action_label(
+    bindings: &Bindings,
+    action: InputAction,
+) -> String {
+    format!("{}: {}", action.name(), bindings.describe(action))
+}
+
+/// Lets the player rebind every [`InputAction`]. An input that
+/// already does another action is only moved over once it's
+/// pressed a second time, so that nothing gets unbound by
+/// accident
+pub struct Controls {
+    menu: Menu<ControlsEntry>,
+    hint: Text,
+    /// The action waiting for an input to be bound to it
+    listening: Option<InputAction>,
+    /// An input that was pressed while listening, but already
+    /// does another action
+    conflict: Option<Binding>,
+}
+
+impl Controls {
+    pub fn new(ctx: &mut Context, shared: &Shared) -> Self {
+        let bindings = &shared.config.controls.bindings;
+        let labels: Vec<_> = InputAction::ALL
+            .iter()
+            .map(|&action| {
+                (
+                    ControlsEntry::Action(action),
+                    action_label(bindings, action),
+                )
+            })
+            .collect();
+
+        let mut entries: Vec<_> = labels
+            .iter()
+            .map(|(entry, label)| (*entry, label.as_str()))
+            .collect();
+        entries
+            .push((ControlsEntry::Reset, "Reset to defaults"));
+        entries.push((ControlsEntry::Back, "Back"));
+
+        Self {
+            menu: Menu::new(ctx, shared, "Controls", &entries),
+            hint: Text::new(
+                DEFAULT_HINT,
+                shared.fonts.small.clone(),
+            ),
+            listening: None,
+            conflict: None,
+        }
+    }
+
+    /// Shows the bindings of every action again
+    fn refresh_labels(
+        &mut self,
+        ctx: &mut Context,
+        bindings: &Bindings,
+    ) {
+        for action in InputAction::ALL {
+            self.menu.set_label(
+                ctx,
+                ControlsEntry::Action(action),
+                &action_label(bindings, action),
+            );
+        }
+    }
+
+    fn stop_listening(&mut self, hint: &str) {
+        self.listening = None;
+        self.conflict = None;
+        self.hint.set_content(hint);
+    }
+
+    /// Binds the next input pressed to `action`
+    fn listen(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+        action: InputAction,
+    ) {
+        let Some(binding) =
+            Binding::pressed(ctx, &shared.gamepads)
+        else {
+            return;
+        };
+
+        if binding == Binding::Key(Key::Escape) {
+            self.stop_listening(DEFAULT_HINT);
+            return;
+        }
+        if binding == Binding::Key(CONSOLE_KEY) {
+            self.stop_listening(&format!(
+                "{binding} is reserved for the console"
+            ));
+            return;
+        }
+
+        let bindings = &mut shared.config.controls.bindings;
+        match bindings.action_of(binding) {
+            Some(other) if other == action => {
+                self.stop_listening(&format!(
+                    "{binding} already does {}",
+                    action.name()
+                ));
+            }
+            Some(other) if self.conflict != Some(binding) => {
+                self.conflict = Some(binding);
+                self.hint.set_content(format!(
+                    "{binding} does {}, press it again to move it",
+                    other.name()
+                ));
+            }
+            _ => {
+                bindings.bind(action, binding);
+                self.refresh_labels(ctx, bindings);
+                self.stop_listening(DEFAULT_HINT);
+            }
+        }
+    }
+
+    /// Saves the bindings on the way out
+    fn leave(shared: &Shared) -> Transition {
+        shared.config.save();
+        Transition::Pop
+    }
+}
+
+impl Scene for Controls {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        if let Some(action) = self.listening {
+            self.listen(ctx, shared, action);
+            return Ok(Transition::None);
+        }
+
+        if input::is_key_pressed(ctx, Key::Escape) {
+            return Ok(Self::leave(shared));
+        }
+
+        let bindings = &mut shared.config.controls.bindings;
+        if input::is_key_pressed(ctx, Key::Backspace)
+            || input::is_key_pressed(ctx, Key::Delete)
+        {
+            if let ControlsEntry::Action(action) =
+                self.menu.selected()
+            {
+                bindings.clear(action);
+                self.refresh_labels(ctx, bindings);
+            }
+        }
+
+        let transition = match self.menu.update(ctx) {
+            Some(ControlsEntry::Action(action)) => {
+                self.listening = Some(action);
+                self.hint.set_content(LISTENING_HINT);
+
+                Transition::None
+            }
+            Some(ControlsEntry::Reset) => {
+                *bindings = Bindings::default();
+                self.refresh_labels(ctx, bindings);
+
+                Transition::None
+            }
+            Some(ControlsEntry::Back) => Self::leave(shared),
+            None => Transition::None,
+        };
+
+        Ok(transition)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        shared.renderer.draw_background(ctx);
+        self.menu.draw(ctx);
+
+        // The hint goes right below the menu
+        if let Some(bounds) = self.hint.get_bounds(ctx) {
+            let position = Vec2::new(
+                (WIDTH as f32 - bounds.width) / 2.0,
+                (HEIGHT as f32 + self.menu.height()) / 2.0
+                    + HINT_SPACING,
+            );
+            self.hint.draw(ctx, position);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+/// How hard a run is. Every preset scales the same handful of
+/// knobs, with `Normal` leaving them as they always were
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    /// Every difficulty, from the easiest to the hardest
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    /// The opposite of [`Difficulty::name`], ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|difficulty| {
+            difficulty.name().eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// The next difficulty up, going back to the easiest one
+    /// after the hardest
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// The one before [`Difficulty::next`]
+    pub fn previous(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1)
+            % Self::ALL.len()]
+    }
+
+    /// How many hearts the player starts with
+    pub fn player_hearts(self) -> u8 {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 2,
+            Difficulty::Nightmare => 1,
+        }
+    }
+
+    /// For how long (in seconds) the player can't be hit again
+    /// after being hit
+    pub fn invulnerability_time(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.0,
+            Difficulty::Normal => 0.5,
+            Difficulty::Hard => 0.35,
+            Difficulty::Nightmare => 0.2,
+        }
+    }
+
+    /// How many times more hearts enemies spawn with
+    pub fn enemy_health(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    /// How many times more often enemies shoot
+    pub fn enemy_fire_rate(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    /// How many times longer it takes for a power-up to spawn
+    pub fn power_up_interval(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    /// How many times more points every kill is worth
+    pub fn score_multiplier(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        clock::GameClock, enemy::EnemyManager,
+        humanoid::HumanoidType, simulation::GameRng,
+    };
+
+    const KINDS: [HumanoidType; 4] = [
+        HumanoidType::BasicEnemy,
+        HumanoidType::StrongerEnemy,
+        HumanoidType::BadassEnemy,
+        HumanoidType::Boss,
+    ];
+
+    /// The hearts of every kind of enemy, as spawned on
+    /// `difficulty`
+    fn hearts_on(difficulty: Difficulty) -> Vec<u8> {
+        let clock = GameClock::new();
+        let mut enemy_mgr = EnemyManager::new(
+            &clock,
+            Duration::from_secs(1),
+            difficulty,
+        );
+        let mut rng = GameRng::seed_from_u64(1);
+
+        for kind in KINDS {
+            enemy_mgr.spawn_enemy(kind, &mut rng, &clock);
+        }
+
+        enemy_mgr
+            .enemies
+            .iter()
+            .map(|enemy| enemy.hearts)
+            .collect()
+    }
+
+    #[test]
+    fn enemy_hearts_follow_the_difficulty() {
+        assert_eq!(hearts_on(Difficulty::Easy), [1, 1, 2, 7]);
+        assert_eq!(hearts_on(Difficulty::Normal), [1, 2, 3, 10]);
+        assert_eq!(hearts_on(Difficulty::Hard), [2, 3, 5, 15]);
+        assert_eq!(
+            hearts_on(Difficulty::Nightmare),
+            [2, 4, 6, 20]
+        );
+    }
+
+    #[test]
+    fn enemy_fire_rate_follows_the_difficulty() {
+        let margin = Duration::from_millis(1);
+
+        for (difficulty, wait_time) in [
+            (Difficulty::Easy, 1.0 / 0.6),
+            (Difficulty::Normal, 1.0),
+            (Difficulty::Hard, 1.0 / 1.4),
+            (Difficulty::Nightmare, 0.5),
+        ] {
+            let wait_time = Duration::from_secs_f64(wait_time);
+            let mut clock = GameClock::new();
+            let mut enemy_mgr = EnemyManager::new(
+                &clock,
+                Duration::from_secs(1),
+                difficulty,
+            );
+            enemy_mgr.spawn_enemy(
+                HumanoidType::StrongerEnemy,
+                &mut GameRng::seed_from_u64(1),
+                &clock,
+            );
+            let enemy = &enemy_mgr.enemies[0];
+
+            clock.advance(wait_time - margin);
+            assert!(
+                !enemy.can_fire(&clock),
+                "{} enemies fire too soon",
+                difficulty.name()
+            );
+
+            clock.advance(margin * 2);
+            assert!(
+                enemy.can_fire(&clock),
+                "{} enemies fire too late",
+                difficulty.name()
+            );
+        }
+    }
+}
@@ -4,15 +4,20 @@ use rand::{
     distributions::Uniform, prelude::Distribution,
     seq::SliceRandom, Rng,
 };
-use tetra::{graphics::Rectangle, math::Vec2, Context};
+use tetra::math::Vec2;
 
 use crate::{
-    animation::CannonballAnimation,
+    clock::GameClock,
     debug_println,
-    humanoid::{Humanoid, HumanoidType},
+    difficulty::Difficulty,
+    event::GameEvent,
+    hitbox::Collider,
+    humanoid::{Humanoid, HumanoidType, ShootingBehavior},
     oneoffanim::OneOffAnimationManager,
-    projectile::{Projectile, ProjectileManager},
-    textures::GruntTextures,
+    projectile::{
+        Projectile, ProjectileManager, CANNONBALL_HITBOX,
+    },
+    spatial::SpatialGrid,
     timer::Timer,
     traits::Cleanable,
 };
@@ -22,12 +27,15 @@ pub struct EnemyManager {
     pub enemies: Vec<Humanoid>,
     /// Times the interval in which enemies can be spawned
     spawn_timer: Timer,
-    /// Average enemy velocity
+    /// Average enemy velocity, in pixels per second
     avg_enemy_vel: f32,
     /// Spawns and cleans up projectiles coming from enemies
     projectile_mgr: ProjectileManager,
-    /// All enemy textures already loaded into memory
-    textures: GruntTextures,
+    /// How many times more hearts new enemies get
+    health_multiplier: f64,
+    /// How many times faster cannonballs fly
+    projectile_speed_multiplier: f32,
+    difficulty: Difficulty,
 }
 
 impl Cleanable for EnemyManager {
@@ -46,23 +54,44 @@ impl Cleanable for EnemyManager {
 }
 
 impl EnemyManager {
-    pub fn new(ctx: &mut Context) -> Self {
-        let cannonball_animation =
-            CannonballAnimation::build(ctx);
-
+    pub fn new(
+        clock: &GameClock,
+        spawn_interval: Duration,
+        difficulty: Difficulty,
+    ) -> Self {
         Self {
             enemies: Vec::with_capacity(24),
-            avg_enemy_vel: 1.0,
+            avg_enemy_vel: 60.0,
             spawn_timer: Timer::start_now_with_interval(
-                Duration::from_secs_f64(1.5),
+                clock,
+                spawn_interval,
             ),
             projectile_mgr: ProjectileManager::new(
-                cannonball_animation,
+                CANNONBALL_HITBOX,
             ),
-            textures: GruntTextures::load(ctx),
+            health_multiplier: 1.0,
+            projectile_speed_multiplier: 1.0,
+            difficulty,
         }
     }
 
+    /// Changes how long to wait between spawning two enemies
+    pub fn set_spawn_interval(&mut self, interval: Duration) {
+        self.spawn_timer.set_interval(interval);
+    }
+
+    /// Makes enemies spawned from now on tougher and every
+    /// cannonball faster, by the given factors
+    pub fn set_difficulty(
+        &mut self,
+        health: f64,
+        projectile_speed: f64,
+    ) {
+        self.health_multiplier = health;
+        self.projectile_speed_multiplier =
+            projectile_speed as f32;
+    }
+
     fn generate_spawn_location<R: Rng>(
         rng: &mut R,
     ) -> (f32, f32) {
@@ -99,8 +128,9 @@ impl EnemyManager {
         &mut self,
         kind: HumanoidType,
         rng: &mut R,
+        clock: &GameClock,
     ) {
-        self.spawn_timer.reset();
+        self.spawn_timer.reset(clock);
 
         let (lives, allowed_to_shoot, shooting_wait_time) =
             match kind {
@@ -119,12 +149,26 @@ impl EnemyManager {
                 }
             };
 
-        let texture =
-            self.textures.choose_enemy_from_kind(kind, rng);
+        let multiplier = self.health_multiplier
+            * self.difficulty.enemy_health();
+        let lives = lives as f64 * multiplier;
+        // Rounding would leave the weaker enemies as tough as
+        // ever, so fewer hearts are always rounded down
+        let lives = if multiplier < 1.0 {
+            lives.floor()
+        } else {
+            lives.round()
+        };
+        let lives = lives.clamp(1.0, u8::MAX as f64) as u8;
+        let shooting_wait_time = shooting_wait_time
+            .div_f64(self.difficulty.enemy_fire_rate());
+
+        // Choose a random sprite of the given enemy kind
+        let sprite = rng.gen_range(0..kind.sprite_variants());
 
         let enemy_vel = Vec2::new(
-            rng.gen_range(0.3..0.7) + self.avg_enemy_vel,
-            rng.gen_range(0.3..0.7) + self.avg_enemy_vel,
+            rng.gen_range(18.0..42.0) + self.avg_enemy_vel,
+            rng.gen_range(18.0..42.0) + self.avg_enemy_vel,
         );
 
         self.avg_enemy_vel +=
@@ -133,45 +177,63 @@ impl EnemyManager {
         let (x, y) = Self::generate_spawn_location(rng);
 
         let enemy = Humanoid::new(
+            clock,
             lives,
-            texture,
+            sprite,
             Vec2::new(x, y),
             enemy_vel,
-            allowed_to_shoot,
-            shooting_wait_time,
+            ShootingBehavior::new(
+                clock,
+                allowed_to_shoot,
+                shooting_wait_time,
+            ),
             kind,
         );
         self.enemies.push(enemy);
     }
 
-    pub fn can_spawn(&self) -> bool {
-        self.spawn_timer.is_ready()
+    pub fn can_spawn(&self, clock: &GameClock) -> bool {
+        self.spawn_timer.is_ready(clock)
+    }
+
+    pub fn cannonballs(&self) -> &[Projectile] {
+        self.projectile_mgr.projectiles()
+    }
+
+    pub fn cannonball_colliders(
+        &self,
+    ) -> impl Iterator<Item = Collider> + '_ {
+        self.projectile_mgr.colliders()
     }
 
+    /// Advances all enemies and their cannonballs up to the
+    /// current game time
     pub fn update(
         &mut self,
-        ctx: &mut Context,
         player_pos: Vec2<f32>,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
     ) {
+        let dt = clock.delta_secs();
+
         // Clean up dead enemies
         self.clean_up();
 
-        self.projectile_mgr.advance_animation(ctx);
+        self.projectile_mgr.advance(dt);
 
         for enemy in &mut self.enemies {
-
             let (
                 is_fast_shooting,
                 is_fast_running,
                 is_triple_shooting,
             ) = enemy.power_ups.currently_active();
             let velocity = if is_fast_shooting {
-                Vec2 { x: 7.5, y: 7.5 }
+                Vec2 { x: 450.0, y: 450.0 }
             } else {
-                Vec2 { x: 4.5, y: 4.5 }
-            };
+                Vec2 { x: 270.0, y: 270.0 }
+            } * self.projectile_speed_multiplier;
 
-            if enemy.can_fire() {
+            if enemy.can_fire(clock) {
                 let angle_to_player_deg =
                     enemy.angle_to_pos(player_pos).to_degrees();
                 self.projectile_mgr.shoot(
@@ -180,43 +242,47 @@ impl EnemyManager {
                     enemy.position,
                     velocity,
                 );
-                enemy.shooting_behavior.register_fire();
+                enemy.shooting_behavior.register_fire(clock);
+                events.push(GameEvent::EnemyFired);
             }
 
             // Advance the animation of all enemies and update
             // their locations
-            enemy.advance_animation(ctx);
-            enemy.head_to(is_fast_running, player_pos);
+            enemy.advance_animation(clock);
+            enemy.head_to(is_fast_running, player_pos, dt);
         }
     }
 
+    /// Checks every fireball against the enemies it's close
+    /// to, `enemy_grid` holding the enemies' colliders
     pub fn check_for_fireball_collisions(
         &mut self,
-        enemy_rects: &[Rectangle],
-        fireballs: &[Projectile],
+        enemy_grid: &SpatialGrid,
+        fireballs: impl Iterator<Item = Collider>,
         one_off_anim_mgr: &mut OneOffAnimationManager,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
     ) {
-        let fireball_rects = fireballs
-            .iter()
-            .map(Projectile::position)
-            .map(Vec2::into_tuple)
-            .map(|(x, y)| {
-                Rectangle::new(x + 5.0, y + 5.0, 32.0, 32.0)
-            });
-
-        for fireball in fireball_rects {
-            for (enemy, enemy_rect) in
-                self.enemies.iter_mut().zip(enemy_rects)
-            {
-                if enemy_rect.intersects(&fireball) {
-                    let (x, y) =
-                        (fireball.x + 5.0, fireball.y + 5.0);
+        for fireball in fireballs {
+            enemy_grid.for_each_intersecting(
+                &fireball,
+                |enemy| {
                     one_off_anim_mgr
-                        .add_explosion(Vec2 { x, y });
+                        .add_explosion(fireball.center(), clock);
 
+                    let enemy = &mut self.enemies[enemy];
+                    let hearts = enemy.hearts;
                     enemy.take_hit();
-                }
-            }
+
+                    if enemy.hearts < hearts {
+                        events.push(if enemy.is_dead() {
+                            GameEvent::EnemyDied
+                        } else {
+                            GameEvent::EnemyHit
+                        });
+                    }
+                },
+            );
         }
     }
 
@@ -224,27 +290,21 @@ impl EnemyManager {
         &self,
         player: &mut Humanoid,
         one_off_anim_mgr: &mut OneOffAnimationManager,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
     ) {
-        let player_rect = player.rectangle();
-        for cannon in self.projectile_mgr.projectiles() {
-            let cannon_pos = cannon.position();
-            let cannon_rect = Rectangle::new(
-                cannon_pos.x,
-                cannon_pos.y,
-                16.0,
-                16.0,
-            );
-            if cannon_rect.intersects(&player_rect) {
+        let player_collider = player.collider();
+        for cannonball in self.projectile_mgr.colliders() {
+            if cannonball.intersects(&player_collider) {
+                let hearts = player.hearts;
                 player.take_hit();
-                one_off_anim_mgr.add_smoke(cannon_pos);
-            }
-        }
-    }
+                if player.hearts < hearts {
+                    events.push(GameEvent::CannonballImpact);
+                }
 
-    pub fn draw(&mut self, ctx: &mut Context) {
-        for enemy in self.enemies.iter_mut() {
-            enemy.draw(ctx);
+                one_off_anim_mgr
+                    .add_smoke(cannonball.center(), clock);
+            }
         }
-        self.projectile_mgr.draw(ctx);
     }
 }
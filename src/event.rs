@@ -0,0 +1,18 @@
+/// Something that happened during a tick of the simulation,
+/// which the player gets to hear about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    PlayerFired,
+    EnemyFired,
+    /// An enemy was hit by a fireball but survived it
+    EnemyHit,
+    EnemyDied,
+    /// The player lost a heart
+    PlayerHurt,
+    CannonballImpact,
+    PowerUpPickedUp,
+    /// One of the player's power-ups wore off
+    PowerUpExpired,
+    WaveStarted,
+    GameOver,
+}
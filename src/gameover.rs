@@ -0,0 +1,149 @@
+use rand::{rngs::OsRng, Rng};
+use tetra::{
+    input::{self, Key},
+    Context,
+};
+
+use crate::{
+    input::InputSource,
+    playing::Playing,
+    scene::{Scene, Shared, Transition},
+    scoretable::{sanitize_name, HighScore, MAX_NAME_LEN},
+    title::Title,
+};
+
+/// The game over panel, drawn over the last frame of the run.
+/// It also shows up when a replay runs out before the player
+/// dies
+pub struct GameOver {
+    /// What the panel says happened
+    title: &'static str,
+    /// The seed of the run that just ended
+    seed: u64,
+    /// The run, while the player types the name it'll be saved
+    /// in the high score table with
+    naming: Option<HighScore>,
+}
+
+impl GameOver {
+    /// `run` is the run that just ended, if it may go into the
+    /// high score table
+    pub fn new(
+        shared: &Shared,
+        seed: u64,
+        run: Option<HighScore>,
+    ) -> Self {
+        let naming = run.filter(|run| {
+            shared
+                .score_table
+                .qualifies(run.score, run.difficulty)
+        });
+
+        Self {
+            title: "Game over!",
+            seed,
+            naming,
+        }
+    }
+
+    /// The end of a replay that ran out while the player was
+    /// still alive
+    pub fn replay_finished(seed: u64) -> Self {
+        Self {
+            title: "Replay finished",
+            seed,
+            naming: None,
+        }
+    }
+
+    /// Reads the name of the run being saved as it's typed,
+    /// returning whether the player is done with it
+    fn type_name(ctx: &Context, run: &mut HighScore) -> bool {
+        if let Some(text) = input::get_text_input(ctx) {
+            run.name.push_str(text);
+            run.name =
+                run.name.chars().take(MAX_NAME_LEN).collect();
+        }
+
+        if input::is_key_pressed(ctx, Key::Backspace) {
+            run.name.pop();
+        }
+
+        input::is_key_pressed(ctx, Key::Enter)
+    }
+}
+
+impl Scene for GameOver {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        if let Some(run) = &mut self.naming {
+            // Escape leaves the run out of the table
+            if input::is_key_pressed(ctx, Key::Escape) {
+                self.naming = None;
+            } else if Self::type_name(ctx, run) {
+                let mut run = self.naming.take().unwrap();
+                run.name = match sanitize_name(&run.name) {
+                    name if name.is_empty() => {
+                        "Anonymous".into()
+                    }
+                    name => name,
+                };
+
+                shared.score_table.insert(run);
+            }
+
+            // Keys typed into the name don't count as choices
+            return Ok(Transition::None);
+        }
+
+        // Retrying only rebuilds the game logic, since every
+        // texture is kept in `shared`
+        if input::is_key_pressed(ctx, Key::R) {
+            return Ok(Transition::Switch(Box::new(
+                Playing::new(
+                    shared,
+                    OsRng.gen(),
+                    InputSource::Keyboard(None),
+                ),
+            )));
+        }
+
+        if input::is_key_pressed(ctx, Key::Enter) {
+            return Ok(Transition::Switch(Box::new(
+                Title::new(ctx, shared),
+            )));
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        let hint = match &self.naming {
+            Some(run) => {
+                format!("High score! Name: {}_", run.name)
+            }
+            None => "Press R to retry / Enter for menu".into(),
+        };
+
+        shared.renderer.draw_game_over_panel(
+            ctx, self.title, self.seed, &hint,
+        );
+
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn ducks_music(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,242 @@
+//! Twin-stick controls: the left stick moves and the right
+//! stick aims and fires.
+//!
+//! Only one gamepad is used at a time. The first one plugged in
+//! is picked, and once it's unplugged another one that's still
+//! connected takes over.
+
+use std::time::Duration;
+
+use tetra::{
+    graphics::{
+        text::{Font, Text},
+        Color, DrawParams,
+    },
+    input::{self, GamepadButton, GamepadStick},
+    math::Vec2,
+    Context, Event,
+};
+
+use crate::{clock::GameClock, HEIGHT, WIDTH};
+
+/// How far the left stick has to be pushed before the player
+/// moves, out of 1
+const MOVE_DEADZONE: f32 = 0.2;
+/// How far the right stick has to be pushed before the player
+/// fires, which is further than for moving so that a resting
+/// thumb doesn't fire
+const AIM_DEADZONE: f32 = 0.5;
+
+/// How many gamepad slots are looked through for a replacement
+/// once the active gamepad is unplugged
+const MAX_GAMEPADS: usize = 8;
+
+/// How long the indicator stands out after a gamepad was
+/// plugged in or out
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+const NOTICE_COLOR: Color = Color::WHITE;
+const INDICATOR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+const MARGIN: f32 = 16.0;
+
+/// The gamepad the player is using
+struct ActivePad {
+    id: usize,
+    name: String,
+}
+
+/// Keeps track of the gamepad in use and shows which one it is
+pub struct Gamepads {
+    active: Option<ActivePad>,
+    text: Text,
+    /// When a gamepad was last plugged in or out, in real time
+    changed_at: Option<Duration>,
+}
+
+impl Gamepads {
+    pub fn new(font: &Font) -> Self {
+        Self {
+            active: None,
+            text: Text::new("", font.clone()),
+            changed_at: None,
+        }
+    }
+
+    /// Picks up gamepads being plugged in and out
+    pub fn event(
+        &mut self,
+        ctx: &Context,
+        event: &Event,
+        clock: &GameClock,
+    ) {
+        match *event {
+            Event::GamepadAdded { id }
+                if self.active.is_none() =>
+            {
+                self.activate(ctx, id);
+            }
+            Event::GamepadRemoved { id }
+                if self.active.as_ref().map(|pad| pad.id)
+                    == Some(id) =>
+            {
+                let pad = self.active.take().expect(
+                    "the removed gamepad should be active",
+                );
+                println!(
+                    "[LOG] Gamepad disconnected: {}",
+                    pad.name
+                );
+                self.text.set_content(format!(
+                    "{} disconnected",
+                    pad.name
+                ));
+
+                if let Some(other) =
+                    (0..MAX_GAMEPADS).find(|&id| {
+                        input::is_gamepad_connected(ctx, id)
+                    })
+                {
+                    self.activate(ctx, other);
+                }
+            }
+            _ => return,
+        }
+
+        self.changed_at = Some(clock.now());
+    }
+
+    fn activate(&mut self, ctx: &Context, id: usize) {
+        let name = input::get_gamepad_name(ctx, id)
+            .unwrap_or_else(|| "Gamepad".to_owned());
+
+        println!("[LOG] Gamepad connected: {name}");
+        self.text.set_content(format!("{name} connected"));
+        self.active = Some(ActivePad { id, name });
+    }
+
+    /// How the left stick wants the player to move. Past the
+    /// deadzone, the stick is rescaled so that movement starts
+    /// from nothing instead of jumping ahead
+    pub fn movement(&self, ctx: &Context) -> Vec2<f32> {
+        let Some(pad) = &self.active else {
+            return Vec2::zero();
+        };
+
+        let stick = input::get_gamepad_stick_position(
+            ctx,
+            pad.id,
+            GamepadStick::LeftStick,
+        );
+        let magnitude = stick.magnitude();
+        if magnitude <= MOVE_DEADZONE {
+            return Vec2::zero();
+        }
+
+        let scaled = ((magnitude - MOVE_DEADZONE)
+            / (1.0 - MOVE_DEADZONE))
+            .min(1.0);
+
+        stick / magnitude * scaled
+    }
+
+    /// The angle (in degrees) the right stick points at, if
+    /// it's pushed far enough to fire
+    pub fn fire_angle(&self, ctx: &Context) -> Option<f32> {
+        let pad = self.active.as_ref()?;
+
+        let stick = input::get_gamepad_stick_position(
+            ctx,
+            pad.id,
+            GamepadStick::RightStick,
+        );
+        if stick.magnitude() <= AIM_DEADZONE {
+            return None;
+        }
+
+        // The stick's y axis points down, like the screen's,
+        // while angles go counterclockwise from the right
+        Some(
+            (-stick.y)
+                .atan2(stick.x)
+                .to_degrees()
+                .rem_euclid(360.0),
+        )
+    }
+
+    pub fn is_button_down(
+        &self,
+        ctx: &Context,
+        button: GamepadButton,
+    ) -> bool {
+        self.active.as_ref().is_some_and(|pad| {
+            input::is_gamepad_button_down(ctx, pad.id, button)
+        })
+    }
+
+    /// The buttons pressed since the last update
+    pub fn buttons_pressed<'a>(
+        &self,
+        ctx: &'a Context,
+    ) -> impl Iterator<Item = GamepadButton> + 'a {
+        self.active
+            .as_ref()
+            .map(|pad| {
+                input::get_gamepad_buttons_pressed(ctx, pad.id)
+            })
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    pub fn is_button_pressed(
+        &self,
+        ctx: &Context,
+        button: GamepadButton,
+    ) -> bool {
+        self.active.as_ref().is_some_and(|pad| {
+            input::is_gamepad_button_pressed(ctx, pad.id, button)
+        })
+    }
+
+    /// Shows the gamepad in use at the bottom of the screen,
+    /// standing out for a while after it changed. A gamepad
+    /// being unplugged is only shown for that while
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        clock: &GameClock,
+    ) {
+        let is_recent =
+            self.changed_at.is_some_and(|changed_at| {
+                clock.now().saturating_sub(changed_at)
+                    < NOTICE_DURATION
+            });
+
+        let color = match (&self.active, is_recent) {
+            (_, true) => NOTICE_COLOR,
+            (Some(pad), false) => {
+                if self.changed_at.take().is_some() {
+                    self.text.set_content(format!(
+                        "Gamepad: {}",
+                        pad.name
+                    ));
+                }
+                INDICATOR_COLOR
+            }
+            (None, false) => return,
+        };
+
+        let Some(bounds) = self.text.get_bounds(ctx) else {
+            return;
+        };
+        let position = Vec2::new(
+            (WIDTH as f32 - bounds.width) / 2.0,
+            HEIGHT as f32 - MARGIN - bounds.height,
+        );
+
+        self.text.draw(
+            ctx,
+            DrawParams::new().position(position).color(color),
+        );
+    }
+}
@@ -1,396 +1,291 @@
-use std::time::{Duration, Instant};
+use std::{rc::Rc, time::Instant};
 
-use rand::{
-    prelude::{SliceRandom, SmallRng},
-    SeedableRng,
-};
 use tetra::{
     graphics,
     graphics::scaling::{ScalingMode, ScreenScaler},
-    input::{self, Key},
     time, window, Context, Event, State,
 };
 
 use crate::{
-    background::Background, enemy::EnemyManager,
-    healthbar::HealthBar, humanoid::HumanoidType,
-    oneoffanim::OneOffAnimationManager, panel::GameOverPanel,
-    player::PlayerManager, powerup::PowerUpManager,
-    timer::Timer, HEIGHT, WIDTH,
+    bindings::InputAction,
+    cli::Args,
+    clock::GameClock,
+    config::Config,
+    gamepad::Gamepads,
+    overlay::{DebugOverlay, FrameStage},
+    panel::Panel,
+    renderer::Renderer,
+    scene::{Fonts, Scene, Shared, Transition},
+    scoretable::ScoreTable,
+    sound::SoundManager,
+    waves::Waves,
+    HEIGHT, WIDTH,
 };
 
-/// Enemy types and their spawn rate percentages for each wave
-const WAVES: &[[(HumanoidType, f32); 4]] = &[
-    [
-        (HumanoidType::BasicEnemy, 0.85),
-        (HumanoidType::StrongerEnemy, 0.10),
-        (HumanoidType::BadassEnemy, 0.05),
-        (HumanoidType::Boss, 0.0),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.75),
-        (HumanoidType::StrongerEnemy, 0.20),
-        (HumanoidType::BadassEnemy, 0.05),
-        (HumanoidType::Boss, 0.0),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.75),
-        (HumanoidType::StrongerEnemy, 0.20),
-        (HumanoidType::BadassEnemy, 0.05),
-        (HumanoidType::Boss, 0.0),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.4),
-        (HumanoidType::StrongerEnemy, 0.5),
-        (HumanoidType::BadassEnemy, 0.1),
-        (HumanoidType::Boss, 0.0),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.1),
-        (HumanoidType::StrongerEnemy, 0.6),
-        (HumanoidType::BadassEnemy, 0.3),
-        (HumanoidType::Boss, 0.0),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.0),
-        (HumanoidType::StrongerEnemy, 0.55),
-        (HumanoidType::BadassEnemy, 0.35),
-        (HumanoidType::Boss, 0.1),
-    ],
-    [
-        (HumanoidType::BasicEnemy, 0.0),
-        (HumanoidType::StrongerEnemy, 0.55),
-        (HumanoidType::BadassEnemy, 0.25),
-        (HumanoidType::Boss, 0.2),
-    ],
-];
-
+/// Drives a stack of [`Scene`]s, of which only the top one is
+/// updated
 pub struct GameState {
-    /// The active screen scaler
-    scaler: ScreenScaler,
-    /// The textures of the game's background
-    background: Background,
-    health_bar: HealthBar,
-    player_manager: PlayerManager,
-    power_up_mgr: PowerUpManager,
-    enemy_mgr: EnemyManager,
-    one_off_anim_mgr: OneOffAnimationManager,
-    game_over_panel: GameOverPanel,
-    rng: SmallRng,
-    game_score: u64,
-    current_wave: u8,
-    /// How long every wave lasts
-    wave_timer: Timer,
-    window_title_update_timer: Timer,
-    #[cfg(debug_assertions)]
-    diagnostics: Diagnostics,
+    /// Never empty while the game is running
+    scenes: Vec<Box<dyn Scene>>,
+    /// What every scene has access to
+    shared: Shared,
+    /// Toggled with F3 by default. Always available in debug
+    /// builds, but only when asked for in release builds
+    overlay: Option<DebugOverlay>,
 }
 
 impl GameState {
-    pub fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+    /// Loads everything the scenes need and then starts from
+    /// the scene built by `first_scene`
+    pub fn new(
+        ctx: &mut Context,
+        waves: Waves,
+        args: &Args,
+        config: Config,
+        first_scene: impl FnOnce(
+            &mut Context,
+            &Shared,
+        ) -> Box<dyn Scene>,
+    ) -> tetra::Result<GameState> {
         let now = Instant::now();
 
-        let game_state = GameState {
-            player_manager: PlayerManager::new(ctx),
-            background: Background::new(ctx),
-            health_bar: HealthBar::new(ctx),
-            power_up_mgr: PowerUpManager::new(ctx),
+        let fonts = Fonts::load(ctx);
+        let gamepads = Gamepads::new(&fonts.small);
+        let mut shared = Shared {
             scaler: ScreenScaler::with_window_size(
                 ctx,
                 WIDTH,
                 HEIGHT,
-                ScalingMode::ShowAll,
+                args.scale_mode.unwrap_or(config.window.scaling),
             )?,
-            game_over_panel: GameOverPanel::new(ctx),
-            enemy_mgr: EnemyManager::new(ctx),
-            one_off_anim_mgr: OneOffAnimationManager::new(ctx),
-            rng: SmallRng::from_entropy(),
-            game_score: 0,
-            current_wave: 0,
-            wave_timer: Timer::start_now_with_interval(
-                Duration::from_secs(30),
-            ),
-            #[cfg(debug_assertions)]
-            diagnostics: Diagnostics::new(),
-            window_title_update_timer:
-                Timer::start_now_with_interval(
-                    Duration::from_secs(1),
-                ),
+            renderer: Renderer::new(ctx),
+            fonts,
+            panel: Panel::new(ctx),
+            sound: SoundManager::new(),
+            score_table: ScoreTable::load(),
+            waves: Rc::new(waves),
+            difficulty: args
+                .difficulty
+                .unwrap_or(config.difficulty),
+            ui_clock: GameClock::new(),
+            gamepads,
+            console_enabled: cfg!(debug_assertions)
+                || args.console,
+            config,
         };
 
+        shared.sound.apply_settings(ctx, shared.config.audio);
+        shared.sound.start_music(ctx);
+
         // How long we took to instantiate all textures into GPU
-        // memory and build the managers
+        // memory
         println!(
             "Built initial GameState in {}ms",
             now.elapsed().as_millis()
         );
 
-        Ok(game_state)
+        let overlay = (cfg!(debug_assertions)
+            || args.debug_overlay)
+            .then(|| DebugOverlay::new(&shared.fonts.small));
+
+        let mut state = GameState {
+            scenes: vec![first_scene(ctx, &shared)],
+            shared,
+            overlay,
+        };
+        state.on_stack_changed(ctx);
+
+        Ok(state)
+    }
+
+    fn apply(
+        &mut self,
+        ctx: &mut Context,
+        transition: Transition,
+    ) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+                match self.scenes.last_mut() {
+                    Some(scene) => {
+                        scene.on_resume(&mut self.shared)
+                    }
+                    None => window::quit(ctx),
+                }
+            }
+            Transition::Switch(scene) => {
+                self.scenes.clear();
+                self.scenes.push(scene);
+            }
+            Transition::Quit => window::quit(ctx),
+        }
+
+        self.on_stack_changed(ctx);
     }
 
-    fn check_for_wave_change(&mut self) {
-        if self.wave_timer.is_ready()
-            && self.current_wave < (WAVES.len() as u8 - 1)
+    /// Ducks the music and hides the cursor as the scenes on
+    /// the stack ask for
+    fn on_stack_changed(&mut self, ctx: &mut Context) {
+        let is_ducked =
+            self.scenes.iter().any(|scene| scene.ducks_music());
+        self.shared.sound.set_ducked(is_ducked);
+
+        let hides_cursor = self
+            .scenes
+            .last()
+            .is_some_and(|scene| scene.hides_cursor());
+        if let Err(err) =
+            window::set_mouse_visible(ctx, !hides_cursor)
         {
-            self.current_wave += 1;
-            self.wave_timer.reset();
-            println!(
-                "Commencing wave {}",
-                self.current_wave + 1
+            eprintln!(
+                "[LOG] Failed to change the cursor: {err}"
             );
         }
     }
 
+    /// Moves on to the next scaling mode, which is remembered
+    /// for the next time the game starts
     fn check_for_scale_change(&mut self, ctx: &mut Context) {
-        if input::is_key_pressed(ctx, Key::F1) {
-            let next = match self.scaler.mode() {
-                ScalingMode::Fixed => ScalingMode::Stretch,
-                ScalingMode::Stretch => ScalingMode::ShowAll,
-                ScalingMode::ShowAll => {
-                    ScalingMode::ShowAllPixelPerfect
-                }
-                ScalingMode::ShowAllPixelPerfect => {
-                    ScalingMode::Crop
-                }
-                ScalingMode::Crop => {
-                    ScalingMode::CropPixelPerfect
-                }
-                ScalingMode::CropPixelPerfect => {
-                    ScalingMode::Fixed
-                }
-                _ => ScalingMode::Fixed,
-            };
+        if self.shared.config.controls.bindings.is_pressed(
+            ctx,
+            &self.shared.gamepads,
+            InputAction::ToggleScaling,
+        ) {
+            let scaler = &mut self.shared.scaler;
+            let next = next_scaling_mode(scaler.mode());
 
             println!("[LOG] Scaling mode changed to {next:?}");
 
-            self.scaler.set_mode(next);
+            scaler.set_mode(next);
+            self.shared.config.window.scaling = next;
+            self.shared.config.save();
         }
     }
+}
+
+/// Every scaling mode the player can pick
+pub const SCALING_MODES: [ScalingMode; 6] = [
+    ScalingMode::Fixed,
+    ScalingMode::Stretch,
+    ScalingMode::ShowAll,
+    ScalingMode::ShowAllPixelPerfect,
+    ScalingMode::Crop,
+    ScalingMode::CropPixelPerfect,
+];
 
-    pub fn is_game_over(&self) -> bool {
-        self.player_manager.is_player_dead()
+/// The scaling mode that comes after `mode` when the player
+/// cycles through them
+pub fn next_scaling_mode(mode: ScalingMode) -> ScalingMode {
+    match mode {
+        ScalingMode::Fixed => ScalingMode::Stretch,
+        ScalingMode::Stretch => ScalingMode::ShowAll,
+        ScalingMode::ShowAll => ScalingMode::ShowAllPixelPerfect,
+        ScalingMode::ShowAllPixelPerfect => ScalingMode::Crop,
+        ScalingMode::Crop => ScalingMode::CropPixelPerfect,
+        ScalingMode::CropPixelPerfect => ScalingMode::Fixed,
+        _ => ScalingMode::Fixed,
     }
 }
 
 impl State for GameState {
     fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
-        #[cfg(debug_assertions)]
-        self.diagnostics.start_polling();
+        if let Some(overlay) = &mut self.overlay {
+            overlay.start_timing();
+        }
 
-        graphics::set_canvas(ctx, self.scaler.canvas());
+        graphics::set_canvas(ctx, self.shared.scaler.canvas());
 
-        self.background.draw(ctx);
+        // Draw the top scene along with every scene it's drawn
+        // over
+        let first_visible = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
 
-        self.player_manager.draw(ctx);
-        self.enemy_mgr.draw(ctx);
-        self.power_up_mgr.draw(
-            ctx,
-            &self.player_manager.player_mut().power_ups,
-        );
-        self.health_bar.draw(ctx, self.player_manager.hearts());
-        self.one_off_anim_mgr.draw(ctx);
+        for scene in &mut self.scenes[first_visible..] {
+            scene.draw(ctx, &mut self.shared)?;
+        }
+
+        self.shared.gamepads.draw(ctx, &self.shared.ui_clock);
 
-        if self.is_game_over() {
-            self.game_over_panel.draw(ctx);
+        if let Some(overlay) = &mut self.overlay {
+            let shown_run = self
+                .scenes
+                .iter()
+                .rev()
+                .find_map(|scene| scene.simulation());
+            overlay.draw(ctx, shown_run)?;
         }
 
         graphics::reset_canvas(ctx);
-        self.scaler.draw(ctx);
+        self.shared.scaler.draw(ctx);
 
-        // Update the window title only once per second
-        if self.window_title_update_timer.is_ready() {
-            self.window_title_update_timer.reset();
-
-            window::set_title(
-                ctx,
-                &format!(
-                    "Endless Trial - {:.0} FPS - Wave: {} - Score: {}",
-                    time::get_fps(ctx),
-                    self.current_wave + 1,
-                    self.game_score
-                ),
-            );
+        if let Some(overlay) = &mut self.overlay {
+            overlay.finish_timing(FrameStage::Draw);
         }
 
-        #[cfg(debug_assertions)]
-        self.diagnostics.finish_polling(PollKind::Drawing);
-
         Ok(())
     }
 
     fn update(&mut self, ctx: &mut Context) -> tetra::Result {
-        #[cfg(debug_assertions)]
-        self.diagnostics.start_polling();
+        if let Some(overlay) = &mut self.overlay {
+            overlay.start_timing();
+        }
 
         // Checks if the player changed the screen scaling method
         self.check_for_scale_change(ctx);
 
-        // Freeze the game logic if the game is over
-        if self.is_game_over() {
-            return Ok(());
-        }
-
-        // Checks if the current wave is over
-        self.check_for_wave_change();
-
-        // Check if the player collided with an enemy.
-        //
-        // We return `enemy_rects` here (Vec of Retangles for
-        // each enemy) in order to reuse it in
-        // `check_for_fireball_collisions`
-        let (collided_with_an_enemy, enemy_rects) = self
-            .player_manager
-            .player_mut()
-            .collided_with_bodies(&self.enemy_mgr.enemies);
-
-        if collided_with_an_enemy {
-            self.player_manager.register_hit();
-        }
-
-        // Check if an enemy was hit with a fireball from the
-        // player
-        self.enemy_mgr.check_for_fireball_collisions(
-            &enemy_rects,
-            self.player_manager.fireballs(),
-            &mut self.one_off_anim_mgr,
-        );
-
-        // Check if the player was hit with a cannonball from an
-        // enemy
-        self.enemy_mgr.check_for_cannonball_collisions(
-            self.player_manager.player_mut(),
-            &mut self.one_off_anim_mgr,
-        );
-
-        // Check if any enemy got a power-up
-        for enemy in self.enemy_mgr.enemies.iter_mut() {
-            self.power_up_mgr.check_for_collision(enemy);
+        if let Some(overlay) = &mut self.overlay {
+            if self.shared.config.controls.bindings.is_pressed(
+                ctx,
+                &self.shared.gamepads,
+                InputAction::ToggleOverlay,
+            ) {
+                overlay.toggle();
+            }
         }
 
-        self.power_up_mgr.advance(
-            &mut self.rng,
-            self.player_manager.player_mut(),
-        );
+        self.shared.ui_clock.advance(time::get_delta_time(ctx));
 
-        self.player_manager.update(ctx);
+        let scene = self
+            .scenes
+            .last_mut()
+            .expect("the scene stack should not be empty");
+        let transition = scene.update(ctx, &mut self.shared)?;
+        self.apply(ctx, transition);
 
-        if self.enemy_mgr.can_spawn() {
-            let kind = WAVES[self.current_wave as usize]
-                .choose_weighted(&mut self.rng, |x| x.1)
-                .expect("WAVES should not be empty")
-                .0;
-            self.enemy_mgr.spawn_enemy(kind, &mut self.rng);
+        if let Some(overlay) = &mut self.overlay {
+            overlay.finish_timing(FrameStage::Update);
         }
 
-        // Calculate the enemy score now that new enemies have
-        // been spawned
-        let enemy_score = self.enemy_mgr.calc_score();
-
-        self.one_off_anim_mgr.update();
-
-        self.enemy_mgr
-            .update(ctx, self.player_manager.player_position());
-
-        self.power_up_mgr.update();
-
-        // If the game score has decreased then enemies have been
-        // killed, which adds to the game score
-        self.game_score +=
-            enemy_score - self.enemy_mgr.calc_score();
-
-        #[cfg(debug_assertions)]
-        self.diagnostics.finish_polling(PollKind::Update);
-
         Ok(())
     }
 
     fn event(
         &mut self,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         event: Event,
     ) -> tetra::Result {
         if let Event::Resized { width, height } = event {
-            self.scaler.set_outer_size(width, height);
-        }
-
-        Ok(())
-    }
-}
-
-#[cfg(debug_assertions)]
-struct Diagnostics {
-    /// How often we print the game diagnostics
-    /// to stdout
-    flush_timer: Timer,
-    /// How much it took in average to draw a new frame
-    /// during the last polling interval
-    avg_draw_time: Duration,
-    /// How much it took in average to update the game state
-    /// during the last polling interval
-    avg_update_time: Duration,
-    current_polling_started: Instant,
-    times_polled: u32,
-}
-
-#[cfg(debug_assertions)]
-impl Diagnostics {
-    pub fn new() -> Self {
-        Self {
-            flush_timer: Timer::start_now_with_interval(
-                Duration::from_secs(15),
-            ),
-            avg_draw_time: Duration::new(0, 0),
-            avg_update_time: Duration::new(0, 0),
-            current_polling_started: Instant::now(),
-            times_polled: 0,
-        }
-    }
-
-    pub fn start_polling(&mut self) {
-        self.current_polling_started = Instant::now()
-    }
-
-    pub fn finish_polling(&mut self, kind: PollKind) {
-        self.times_polled += 1;
-
-        let elapsed = self.current_polling_started.elapsed();
-
-        if self.times_polled == 1 {
-            match kind {
-                PollKind::Drawing => {
-                    self.avg_draw_time = elapsed
-                }
-                PollKind::Update => {
-                    self.avg_update_time = elapsed
-                }
-            }
-            return;
-        }
-
-        match kind {
-            PollKind::Drawing => {
-                self.avg_draw_time += elapsed;
-            }
-            PollKind::Update => {
-                self.avg_update_time += elapsed;
-            }
+            self.shared.scaler.set_outer_size(width, height);
         }
+        self.shared.gamepads.event(
+            ctx,
+            &event,
+            &self.shared.ui_clock,
+        );
 
-        if self.flush_timer.is_ready() {
-            self.flush_timer.reset();
-            let avg_draw_time =
-                self.avg_draw_time / self.times_polled;
-            let avg_update_time =
-                self.avg_update_time / self.times_polled;
+        let scene = self
+            .scenes
+            .last_mut()
+            .expect("the scene stack should not be empty");
+        let transition =
+            scene.event(ctx, &mut self.shared, event)?;
+        self.apply(ctx, transition);
 
-            println!("Last {} frames: avg. draw {}ns, avg. update {}ns", self.times_polled, avg_draw_time.as_nanos(), avg_update_time.as_nanos());
-        }
+        Ok(())
     }
 }
-
-#[cfg(debug_assertions)]
-#[derive(Clone, Copy)]
-enum PollKind {
-    Drawing,
-    Update,
-}
@@ -0,0 +1,66 @@
+//! Runs without a window, for simulating runs in batches.
+//!
+//! The bot plays unless a replay is played back, and once the
+//! run is over a summary of it is printed, so that scripts can
+//! compare seeds, difficulties or wave files.
+
+use std::rc::Rc;
+
+use crate::{
+    cli::Args, input::InputSource, simulation::Simulation,
+    waves::Waves,
+};
+
+/// Simulates a run as fast as possible, until the player dies,
+/// the replay runs out or `--ticks` ticks have passed
+pub fn run(
+    args: &Args,
+    waves: Waves,
+    seed: u64,
+    mut input_source: InputSource,
+) {
+    // The settings aren't read, so that a run plays out the
+    // same on every machine
+    let difficulty = input_source
+        .difficulty()
+        .or(args.difficulty)
+        .unwrap_or_default();
+
+    println!(
+        "Simulating a run with seed {seed} on {}",
+        difficulty.name()
+    );
+
+    let mut sim =
+        Simulation::new(seed, Rc::new(waves), difficulty);
+    if let Some(wave) = args.wave {
+        sim.jump_to_wave(wave);
+    }
+
+    let mut ticks: u64 = 0;
+    while !sim.is_game_over()
+        && args.ticks.is_none_or(|max| ticks < max)
+    {
+        let Some(input) = input_source.next_bot_input(&sim)
+        else {
+            break;
+        };
+
+        sim.tick(&input);
+        ticks += 1;
+    }
+
+    input_source.finish_recording();
+
+    let outcome = if sim.is_game_over() {
+        "Game over"
+    } else {
+        "Stopped"
+    };
+    println!(
+        "{outcome} after {ticks} ticks at wave {} with a score of {} (seed {seed}, {})",
+        sim.current_wave() + 1,
+        sim.game_score(),
+        difficulty.name()
+    );
+}
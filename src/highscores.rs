@@ -0,0 +1,239 @@
+use tetra::{
+    graphics::text::Text,
+    input::{self, Key},
+    math::Vec2,
+    Context,
+};
+
+use crate::{
+    difficulty::Difficulty,
+    scene::{Scene, Shared, Transition},
+    scoretable::HighScore,
+    HEIGHT, WIDTH,
+};
+
+/// The columns of the table, in the order they're drawn
+const COLUMNS: [&str; 7] =
+    ["#", "Name", "Score", "Wave", "Time", "Date", "Seed"];
+
+/// How far apart each row is drawn from the next
+const ROW_SPACING: f32 = 30.0;
+
+/// Empty space between two columns
+const COLUMN_GAP: f32 = 20.0;
+
+/// Empty space between the border of the panel and its text
+const PADDING: f32 = 12.0;
+
+/// How much room is left for the title
+const TITLE_HEIGHT: f32 = 64.0;
+
+/// Formats a run as the cells of its row in the table
+fn row(rank: usize, run: &HighScore) -> [String; 7] {
+    let seconds = run.duration.as_secs();
+
+    [
+        format!("{}.", rank + 1),
+        run.name.clone(),
+        run.score.to_string(),
+        run.wave.to_string(),
+        format!("{}:{:02}", seconds / 60, seconds % 60),
+        run.date_string(),
+        run.seed.to_string(),
+    ]
+}
+
+/// Lists the best runs played on this machine, one difficulty
+/// at a time
+pub struct HighScores {
+    difficulty: Difficulty,
+    title: Text,
+    /// The header comes first, then a row for each run
+    rows: Vec<[Text; 7]>,
+    /// Where each column starts, from the left of the panel
+    column_offsets: [f32; 7],
+    /// Shown instead of the table while it's empty
+    empty_text: Text,
+    /// Tells how to see the other difficulties
+    hint_text: Text,
+    width: f32,
+}
+
+impl HighScores {
+    /// Opens on the table of the difficulty new runs are
+    /// played on
+    pub fn new(ctx: &mut Context, shared: &Shared) -> Self {
+        Self::for_difficulty(ctx, shared, shared.difficulty)
+    }
+
+    fn for_difficulty(
+        ctx: &mut Context,
+        shared: &Shared,
+        difficulty: Difficulty,
+    ) -> Self {
+        let small = &shared.fonts.small;
+        let to_texts = |cells: [String; 7]| {
+            cells.map(|cell| Text::new(cell, small.clone()))
+        };
+
+        let mut rows = vec![to_texts(COLUMNS.map(String::from))];
+        rows.extend(
+            shared
+                .score_table
+                .entries(difficulty)
+                .enumerate()
+                .map(|(rank, run)| to_texts(row(rank, run))),
+        );
+
+        // Make every column as wide as its widest cell
+        let mut column_offsets = [0.0; 7];
+        let mut offset = 0.0;
+        for (column, column_offset) in
+            column_offsets.iter_mut().enumerate()
+        {
+            *column_offset = offset;
+
+            let widest_cell = rows
+                .iter_mut()
+                .filter_map(|row| row[column].get_bounds(ctx))
+                .map(|bounds| bounds.width)
+                .fold(0.0, f32::max);
+            offset += widest_cell + COLUMN_GAP;
+        }
+
+        let mut title = Text::new(
+            format!("High Scores: {}", difficulty.name()),
+            shared.fonts.large.clone(),
+        );
+        let mut empty_text = Text::new(
+            "No runs yet, go play one!",
+            small.clone(),
+        );
+        let mut hint_text = Text::new(
+            "Left/Right: other difficulties",
+            small.clone(),
+        );
+
+        let mut text_width = |text: &mut Text| {
+            text.get_bounds(ctx)
+                .map_or(0.0, |bounds| bounds.width)
+        };
+        let widest_line = (offset - COLUMN_GAP)
+            .max(text_width(&mut title))
+            .max(text_width(&mut empty_text))
+            .max(text_width(&mut hint_text));
+
+        Self {
+            difficulty,
+            title,
+            rows,
+            column_offsets,
+            empty_text,
+            hint_text,
+            width: widest_line + 2.0 * PADDING,
+        }
+    }
+}
+
+impl Scene for HighScores {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        if input::is_key_pressed(ctx, Key::Left)
+            || input::is_key_pressed(ctx, Key::A)
+        {
+            *self = Self::for_difficulty(
+                ctx,
+                shared,
+                self.difficulty.previous(),
+            );
+        } else if input::is_key_pressed(ctx, Key::Right)
+            || input::is_key_pressed(ctx, Key::D)
+        {
+            *self = Self::for_difficulty(
+                ctx,
+                shared,
+                self.difficulty.next(),
+            );
+        }
+
+        let go_back = [Key::Escape, Key::Enter, Key::Backspace]
+            .iter()
+            .any(|&key| input::is_key_pressed(ctx, key));
+
+        if go_back {
+            return Ok(Transition::Pop);
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        shared.renderer.draw_background(ctx);
+
+        let is_empty = self.rows.len() == 1;
+        let lines = if is_empty { 2 } else { self.rows.len() };
+        // One more for the hint
+        let lines = lines + 1;
+
+        let height = PADDING
+            + TITLE_HEIGHT
+            + ROW_SPACING * lines as f32
+            + PADDING;
+        let position = Vec2 {
+            x: (WIDTH as f32 - self.width) / 2.0,
+            y: (HEIGHT as f32 - height) / 2.0,
+        };
+
+        shared.panel.sprite.draw_nine_slice(
+            ctx,
+            &shared.panel.config,
+            self.width,
+            height,
+            position,
+        );
+
+        self.title
+            .draw(ctx, position + Vec2::new(PADDING, PADDING));
+
+        let rows_position = position
+            + Vec2::new(PADDING, PADDING + TITLE_HEIGHT);
+
+        self.hint_text.draw(
+            ctx,
+            rows_position
+                + Vec2::new(
+                    0.0,
+                    ROW_SPACING * (lines - 1) as f32,
+                ),
+        );
+
+        if is_empty {
+            self.empty_text.draw(ctx, rows_position);
+            return Ok(());
+        }
+
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            for (cell, offset) in
+                row.iter_mut().zip(self.column_offsets)
+            {
+                cell.draw(
+                    ctx,
+                    rows_position
+                        + Vec2::new(
+                            offset,
+                            ROW_SPACING * index as f32,
+                        ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
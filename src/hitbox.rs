@@ -0,0 +1,242 @@
+//! The areas entities collide with.
+//!
+//! Every kind of entity has a [`Hitbox`], a shape given
+//! relative to the point it's drawn at, which is placed in the
+//! world as a [`Collider`] whenever collisions are checked.
+
+use tetra::{graphics::Rectangle, math::Vec2};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    /// An axis-aligned box, centered on the hitbox's offset
+    Aabb {
+        width: f32,
+        height: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+}
+
+/// The shape of an entity, and where its center is relative to
+/// the point the entity is drawn at
+#[derive(Debug, Clone, Copy)]
+pub struct Hitbox {
+    pub shape: Shape,
+    pub offset: Vec2<f32>,
+}
+
+impl Hitbox {
+    pub const fn aabb(
+        width: f32,
+        height: f32,
+        offset: Vec2<f32>,
+    ) -> Self {
+        Self {
+            shape: Shape::Aabb { width, height },
+            offset,
+        }
+    }
+
+    pub const fn circle(radius: f32, offset: Vec2<f32>) -> Self {
+        Self {
+            shape: Shape::Circle { radius },
+            offset,
+        }
+    }
+
+    /// This hitbox for an entity drawn at `position`
+    pub fn at(&self, position: Vec2<f32>) -> Collider {
+        let center = position + self.offset;
+
+        match self.shape {
+            Shape::Aabb { width, height } => {
+                Collider::Aabb(Rectangle::new(
+                    center.x - width / 2.0,
+                    center.y - height / 2.0,
+                    width,
+                    height,
+                ))
+            }
+            Shape::Circle { radius } => {
+                Collider::Circle { center, radius }
+            }
+        }
+    }
+}
+
+/// A hitbox placed in the world
+#[derive(Debug, Clone, Copy)]
+pub enum Collider {
+    Aabb(Rectangle),
+    Circle { center: Vec2<f32>, radius: f32 },
+}
+
+impl Collider {
+    /// The smallest rectangle containing the whole collider
+    pub fn bounds(&self) -> Rectangle {
+        match *self {
+            Collider::Aabb(rect) => rect,
+            Collider::Circle { center, radius } => {
+                Rectangle::new(
+                    center.x - radius,
+                    center.y - radius,
+                    2.0 * radius,
+                    2.0 * radius,
+                )
+            }
+        }
+    }
+
+    /// The middle of the collider
+    pub fn center(&self) -> Vec2<f32> {
+        match *self {
+            Collider::Aabb(rect) => Vec2::new(
+                rect.x + rect.width / 2.0,
+                rect.y + rect.height / 2.0,
+            ),
+            Collider::Circle { center, .. } => center,
+        }
+    }
+
+    pub fn intersects(&self, other: &Collider) -> bool {
+        match (*self, *other) {
+            (Collider::Aabb(a), Collider::Aabb(b)) => {
+                a.intersects(&b)
+            }
+            (
+                Collider::Circle {
+                    center: a,
+                    radius: radius_a,
+                },
+                Collider::Circle {
+                    center: b,
+                    radius: radius_b,
+                },
+            ) => {
+                a.distance_squared(b)
+                    < (radius_a + radius_b)
+                        * (radius_a + radius_b)
+            }
+            (
+                Collider::Aabb(rect),
+                Collider::Circle { center, radius },
+            )
+            | (
+                Collider::Circle { center, radius },
+                Collider::Aabb(rect),
+            ) => {
+                // The point of the box closest to the circle
+                let closest = Vec2::new(
+                    center.x.clamp(rect.x, rect.x + rect.width),
+                    center.y.clamp(rect.y, rect.y + rect.height),
+                );
+
+                closest.distance_squared(center)
+                    < radius * radius
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aabb(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) -> Collider {
+        Collider::Aabb(Rectangle::new(x, y, width, height))
+    }
+
+    fn circle(x: f32, y: f32, radius: f32) -> Collider {
+        Collider::Circle {
+            center: Vec2::new(x, y),
+            radius,
+        }
+    }
+
+    /// Checks both orders, since intersecting goes both ways
+    fn intersect(a: Collider, b: Collider) -> bool {
+        let result = a.intersects(&b);
+        assert_eq!(result, b.intersects(&a), "{:?} {:?}", a, b);
+
+        result
+    }
+
+    #[test]
+    fn hitboxes_are_centered_on_their_offset() {
+        let hitbox =
+            Hitbox::aabb(36.0, 44.0, Vec2::new(0.0, 2.0));
+        let collider = hitbox.at(Vec2::new(100.0, 100.0));
+
+        assert_eq!(collider.center(), Vec2::new(100.0, 102.0));
+        assert_eq!(
+            collider.bounds(),
+            Rectangle::new(82.0, 80.0, 36.0, 44.0)
+        );
+
+        let circle = Hitbox::circle(9.0, Vec2::new(1.0, 0.0))
+            .at(Vec2::new(10.0, 10.0));
+        assert_eq!(
+            circle.bounds(),
+            Rectangle::new(2.0, 1.0, 18.0, 18.0)
+        );
+    }
+
+    #[test]
+    fn boxes_intersect_boxes() {
+        let a = aabb(0.0, 0.0, 10.0, 10.0);
+
+        assert!(intersect(a, aabb(5.0, 5.0, 10.0, 10.0)));
+        assert!(intersect(a, aabb(2.0, 2.0, 2.0, 2.0)));
+        assert!(!intersect(a, aabb(20.0, 0.0, 10.0, 10.0)));
+        // Overlapping on one axis only isn't enough
+        assert!(!intersect(a, aabb(5.0, 11.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn circles_intersect_circles() {
+        let a = circle(0.0, 0.0, 5.0);
+
+        assert!(intersect(a, circle(6.0, 8.0, 5.5)));
+        assert!(intersect(a, circle(1.0, 1.0, 1.0)));
+        assert!(!intersect(a, circle(6.0, 8.0, 4.5)));
+    }
+
+    #[test]
+    fn circles_intersect_boxes() {
+        let rect = aabb(0.0, 0.0, 10.0, 10.0);
+
+        // Inside, and over an edge
+        assert!(intersect(rect, circle(5.0, 5.0, 1.0)));
+        assert!(intersect(rect, circle(12.0, 5.0, 3.0)));
+        assert!(!intersect(rect, circle(14.0, 5.0, 3.0)));
+
+        // Past a corner, the circle has to reach the corner
+        // itself, even if it overlaps the box's bounds
+        assert!(intersect(rect, circle(13.0, 14.0, 5.5)));
+        assert!(!intersect(rect, circle(13.0, 14.0, 4.5)));
+        assert!(rect
+            .bounds()
+            .intersects(&circle(13.0, 14.0, 4.5).bounds()));
+    }
+
+    #[test]
+    fn touching_edges_dont_intersect() {
+        let rect = aabb(0.0, 0.0, 10.0, 10.0);
+
+        assert!(!intersect(rect, aabb(10.0, 0.0, 10.0, 10.0)));
+        assert!(!intersect(rect, aabb(0.0, 10.0, 10.0, 10.0)));
+        assert!(!intersect(rect, aabb(10.0, 10.0, 5.0, 5.0)));
+        assert!(!intersect(
+            circle(0.0, 0.0, 5.0),
+            circle(6.0, 8.0, 5.0)
+        ));
+        assert!(!intersect(rect, circle(15.0, 5.0, 5.0)));
+        assert!(!intersect(rect, circle(13.0, 14.0, 5.0)));
+    }
+}
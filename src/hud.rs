@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use tetra::{
+    graphics::{text::Text, Color, DrawParams},
+    math::Vec2,
+    Context,
+};
+
+use crate::{
+    panel::Panel, scene::Shared, simulation::Simulation, WIDTH,
+};
+
+/// How fast the shown score catches up with the real one. The
+/// gap between them shrinks by this rate every second.
+const SCORE_COUNT_UP_RATE: f64 = 6.0;
+
+/// How long (in game time) the "WAVE N" banner stays up
+const BANNER_DURATION: Duration = Duration::from_secs(2);
+
+/// Where the HUD panel is drawn, across from the health bar
+const POSITION: Vec2<f32> = Vec2 { x: 32.0, y: 32.0 };
+
+const LINE_SPACING: f32 = 26.0;
+const PADDING: f32 = 6.0;
+
+/// Shows the score, the wave, how long until the next one and,
+/// past the authored waves, the difficulty tier on top of a run
+pub struct Hud {
+    panel: Panel,
+    score_text: Text,
+    wave_text: Text,
+    countdown_text: Text,
+    /// Only shown once the endless tiers start
+    tier_text: Text,
+    banner_text: Text,
+    /// The score as currently shown, which counts up towards
+    /// the real one
+    shown_score: f64,
+    /// What the texts currently hold, so that they're only
+    /// rebuilt when something changes
+    shown: Option<(u64, u16, Option<u64>, u16)>,
+    /// The wave of the last frame we drew
+    last_wave: Option<u16>,
+    /// When the current banner went up, in game time
+    banner_shown_at: Option<Duration>,
+    /// The game time of the last frame we drew
+    last_drawn: Duration,
+}
+
+impl Hud {
+    pub fn new(shared: &Shared) -> Self {
+        let small = &shared.fonts.small;
+
+        Self {
+            panel: shared.panel.clone(),
+            score_text: Text::new("", small.clone()),
+            wave_text: Text::new("", small.clone()),
+            countdown_text: Text::new("", small.clone()),
+            tier_text: Text::new("", small.clone()),
+            banner_text: Text::new(
+                "",
+                shared.fonts.large.clone(),
+            ),
+            shown_score: 0.0,
+            shown: None,
+            last_wave: None,
+            banner_shown_at: None,
+            last_drawn: Duration::ZERO,
+        }
+    }
+
+    /// Moves the shown score towards the real one, following
+    /// the game clock so that it stops along with the game
+    fn count_up(&mut self, sim: &Simulation) {
+        let now = sim.clock().now();
+        let dt =
+            now.saturating_sub(self.last_drawn).as_secs_f64();
+        self.last_drawn = now;
+
+        let score = sim.game_score() as f64;
+        let gap = score - self.shown_score;
+
+        self.shown_score = if gap < 1.0 {
+            score
+        } else {
+            self.shown_score
+                + gap * (1.0 - (-SCORE_COUNT_UP_RATE * dt).exp())
+        };
+    }
+
+    fn check_for_new_wave(&mut self, sim: &Simulation) {
+        let wave = sim.current_wave();
+        if self.last_wave == Some(wave) {
+            return;
+        }
+
+        self.last_wave = Some(wave);
+        self.banner_shown_at = Some(sim.clock().now());
+        self.banner_text
+            .set_content(format!("WAVE {}", wave + 1));
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, sim: &Simulation) {
+        self.count_up(sim);
+        self.check_for_new_wave(sim);
+
+        let seconds_left =
+            sim.wave_time_left().map(|left| left.as_secs());
+        let shown = (
+            self.shown_score as u64,
+            sim.current_wave(),
+            seconds_left,
+            sim.difficulty_tier(),
+        );
+
+        if self.shown != Some(shown) {
+            let (score, wave, seconds_left, tier) = shown;
+
+            self.score_text
+                .set_content(format!("Score: {score}"));
+            self.wave_text
+                .set_content(format!("Wave {}", wave + 1));
+            self.countdown_text.set_content(
+                match seconds_left {
+                    Some(secs) => {
+                        format!(
+                            "Next wave: {}:{:02}",
+                            secs / 60,
+                            secs % 60
+                        )
+                    }
+                    None => "Final wave".to_owned(),
+                },
+            );
+            self.tier_text
+                .set_content(format!("Endless tier {tier}"));
+
+            self.shown = Some(shown);
+        }
+
+        self.draw_panel(ctx, sim.difficulty_tier() > 0);
+        self.draw_banner(ctx, sim);
+    }
+
+    fn draw_panel(
+        &mut self,
+        ctx: &mut Context,
+        show_tier: bool,
+    ) {
+        let mut lines = vec![
+            &mut self.score_text,
+            &mut self.wave_text,
+            &mut self.countdown_text,
+        ];
+        if show_tier {
+            lines.push(&mut self.tier_text);
+        }
+
+        let width = lines
+            .iter_mut()
+            .filter_map(|text| text.get_bounds(ctx))
+            .map(|bounds| bounds.width)
+            .fold(0.0, f32::max)
+            + 2.0 * PADDING;
+
+        self.panel.sprite.draw_nine_slice(
+            ctx,
+            &self.panel.config,
+            width,
+            lines.len() as f32 * LINE_SPACING + 2.0 * PADDING,
+            POSITION,
+        );
+
+        for (line, text) in lines.into_iter().enumerate() {
+            text.draw(
+                ctx,
+                POSITION
+                    + Vec2::new(
+                        PADDING,
+                        PADDING + LINE_SPACING * line as f32,
+                    ),
+            );
+        }
+    }
+
+    /// Draws the "WAVE N" banner, fading it out as its time runs
+    /// out
+    fn draw_banner(
+        &mut self,
+        ctx: &mut Context,
+        sim: &Simulation,
+    ) {
+        let Some(shown_at) = self.banner_shown_at else {
+            return;
+        };
+
+        let shown_for =
+            sim.clock().now().saturating_sub(shown_at);
+        if shown_for >= BANNER_DURATION {
+            self.banner_shown_at = None;
+            return;
+        }
+
+        let opacity = 1.0
+            - shown_for.as_secs_f32()
+                / BANNER_DURATION.as_secs_f32();
+        let width = self
+            .banner_text
+            .get_bounds(ctx)
+            .map_or(0.0, |bounds| bounds.width);
+
+        self.banner_text.draw(
+            ctx,
+            DrawParams::new()
+                .position(Vec2::new(
+                    (WIDTH as f32 - width) / 2.0,
+                    200.0,
+                ))
+                .color(Color::WHITE.with_alpha(opacity)),
+        );
+    }
+}
@@ -1,19 +1,28 @@
-use std::time::{Duration, Instant};
-
-use tetra::{
-    graphics::{
-        animation::Animation, DrawParams, Rectangle, Texture,
-    },
-    input::{self, Key},
-    math::Vec2,
-    Context,
-};
+use std::time::Duration;
+
+use tetra::math::Vec2;
 
 use crate::{
-    animation::HumanoidAnimation, powerup::ActivePowerUps,
-    traits::Cleanable, Direction, BOUNDS,
+    clock::GameClock,
+    hitbox::{Collider, Hitbox},
+    powerup::ActivePowerUps,
+    resources,
+    timer::Timer,
+    Direction, BOUNDS,
 };
 
+/// How long each frame of the walking animation lasts
+const WALKING_FRAME_LENGTH: Duration =
+    Duration::from_millis(500);
+
+/// For how long (in seconds) a humanoid flickers after being
+/// hit by default, during which it can't be hit again
+const INVULNERABILITY_TIME: f32 = 0.5;
+
+/// How long (in seconds) a flickering sprite stays hidden or
+/// shown before toggling
+pub const FLICKER_INTERVAL: f32 = 1.0 / 60.0;
+
 #[derive(Clone, Copy)]
 pub enum HumanoidType {
     Player,
@@ -23,37 +32,76 @@ pub enum HumanoidType {
     Boss,
 }
 
+/// The player is drawn 42 pixels wide and 48 tall, centered on
+/// their position. Their hitbox is a bit smaller than that, so
+/// that grazing an enemy doesn't feel like a hit
+const PLAYER_HITBOX: Hitbox =
+    Hitbox::aabb(30.0, 40.0, Vec2 { x: 0.0, y: 4.0 });
+
+/// Enemies are drawn just as big as the player
+const ENEMY_HITBOX: Hitbox =
+    Hitbox::aabb(36.0, 44.0, Vec2 { x: 0.0, y: 2.0 });
+
+impl HumanoidType {
+    pub const fn hitbox(self) -> Hitbox {
+        match self {
+            HumanoidType::Player => PLAYER_HITBOX,
+            _ => ENEMY_HITBOX,
+        }
+    }
+
+    /// How many different sprites exist for this kind of
+    /// humanoid
+    pub const fn sprite_variants(self) -> usize {
+        match self {
+            HumanoidType::Player | HumanoidType::Boss => 1,
+            HumanoidType::BasicEnemy => {
+                resources::BASIC_GRUNTS.len()
+            }
+            HumanoidType::StrongerEnemy => {
+                resources::STRONGER_GRUNTS.len()
+            }
+            HumanoidType::BadassEnemy => {
+                resources::BADASS_GRUNTS.len()
+            }
+        }
+    }
+}
+
 pub struct ShootingBehavior {
     /// Determines if the humanoid can shoot
     pub allowed_to_shoot: bool,
-    /// The last moment that this humanoid shot a projectile   
-    last_projectile_thrown_time: Instant,
+    /// The last moment (in game time) that this humanoid shot
+    /// a projectile
+    last_projectile_thrown_time: Duration,
     /// The interval in which this humanoid can shoot
     shooting_wait_time: Duration,
 }
 
 impl ShootingBehavior {
     pub fn new(
+        clock: &GameClock,
         allowed_to_shoot: bool,
         shooting_wait_time: Duration,
     ) -> Self {
         Self {
             allowed_to_shoot,
-            last_projectile_thrown_time: Instant::now(),
+            last_projectile_thrown_time: clock.now(),
             shooting_wait_time,
         }
     }
 
-    pub fn can_fire(&self) -> bool {
-        let time_since_last_thrown =
-            self.last_projectile_thrown_time.elapsed();
+    pub fn can_fire(&self, clock: &GameClock) -> bool {
+        let time_since_last_thrown = clock
+            .now()
+            .saturating_sub(self.last_projectile_thrown_time);
 
         self.allowed_to_shoot
             && time_since_last_thrown >= self.shooting_wait_time
     }
 
-    pub fn register_fire(&mut self) {
-        self.last_projectile_thrown_time = Instant::now();
+    pub fn register_fire(&mut self, clock: &GameClock) {
+        self.last_projectile_thrown_time = clock.now();
     }
 
     pub fn set_shooting_wait_time(
@@ -68,50 +116,52 @@ impl ShootingBehavior {
 pub struct Humanoid {
     pub hearts: u8,
     pub direction: Direction,
-    pub animation: HumanoidAnimation,
+    /// Which of the sprites of its kind this humanoid is drawn
+    /// with
+    pub sprite: usize,
+    /// The current frame of the walking animation
+    pub walking_frame: u8,
+    walking_timer: Timer,
     pub power_ups: ActivePowerUps,
     pub position: Vec2<f32>,
     pub velocity: Vec2<f32>,
     pub shooting_behavior: ShootingBehavior,
     /// Set when the humanoid should 'flicker', such as when the
-    /// player is hit
-    pub flickering: u16,
+    /// player is hit. Holds how many seconds of flickering are
+    /// left
+    pub flickering: f32,
+    /// How many seconds of flickering a hit leaves the humanoid
+    /// with
+    pub invulnerability_time: f32,
+    /// Set by the console's `god` command, in which case hits
+    /// are ignored
+    pub is_invincible: bool,
     pub kind: HumanoidType,
 }
 
-impl Cleanable for Humanoid {
-    fn clean_up(&mut self) {
-        for power_up in self.power_ups.slots.iter_mut() {
-            if let Some(moment_started) = power_up {
-                if moment_started.elapsed()
-                    >= Duration::from_secs(5)
-                {
-                    *power_up = None;
-                }
-            }
-        }
-    }
-}
-
 impl Humanoid {
     pub fn new(
+        clock: &GameClock,
         hearts: u8,
-        texture: Texture,
+        sprite: usize,
         position: Vec2<f32>,
         velocity: Vec2<f32>,
-        allowed_to_shoot: bool,
-        shooting_wait_time: Duration,
+        shooting_behavior: ShootingBehavior,
         kind: HumanoidType,
     ) -> Self {
         Self {
             hearts,
-            flickering: 0,
+            flickering: 0.0,
+            invulnerability_time: INVULNERABILITY_TIME,
+            is_invincible: false,
             direction: Direction::North,
-            animation: HumanoidAnimation::new(texture),
-            shooting_behavior: ShootingBehavior::new(
-                allowed_to_shoot,
-                shooting_wait_time,
+            sprite,
+            walking_frame: 0,
+            walking_timer: Timer::start_now_with_interval(
+                clock,
+                WALKING_FRAME_LENGTH,
             ),
+            shooting_behavior,
             power_ups: ActivePowerUps::new(),
             position,
             velocity,
@@ -119,86 +169,69 @@ impl Humanoid {
         }
     }
 
-    pub fn can_fire(&self) -> bool {
-        self.shooting_behavior.can_fire()
+    /// Drops the power-ups that have worn off
+    pub fn clean_up(&mut self, clock: &GameClock) {
+        self.power_ups.clean_up(clock);
+    }
+
+    pub fn can_fire(&self, clock: &GameClock) -> bool {
+        self.shooting_behavior.can_fire(clock)
     }
 
     pub fn kind(&self) -> HumanoidType {
         self.kind
     }
 
-    pub fn advance_animation(&mut self, ctx: &mut Context) {
-        match self.direction {
-            Direction::North => {
-                self.animation.backside.advance(ctx)
-            }
-            Direction::West | Direction::East => {
-                self.animation.leftside.advance(ctx)
-            }
-            Direction::South => {
-                self.animation.frontside.advance(ctx)
-            }
+    /// Advances the walking animation and the flickering
+    /// counter up to the current game time
+    pub fn advance_animation(&mut self, clock: &GameClock) {
+        if self.walking_timer.is_ready(clock) {
+            self.walking_timer.reset(clock);
+            self.walking_frame = (self.walking_frame + 1) % 2;
         }
-    }
 
-    fn get_animation_ref(&self) -> (&Animation, Vec2<f32>) {
-        let scale = Vec2::new(3., 3.);
-        match self.direction {
-            Direction::North => {
-                (&self.animation.backside, scale)
-            }
-            Direction::West => (&self.animation.leftside, scale),
-            Direction::East => (
-                &self.animation.leftside,
-                Vec2 { x: -3., y: 3. },
-            ),
-            Direction::South => {
-                (&self.animation.frontside, scale)
-            }
-        }
+        self.flickering =
+            (self.flickering - clock.delta_secs()).max(0.0);
     }
 
-    pub fn draw(&mut self, ctx: &mut Context) {
-        if self.flickering > 0 {
-            self.flickering -= 1;
-            if self.flickering % 2 == 0 {
-                return;
-            }
-        }
+    /// Whether or not this humanoid should be drawn in the
+    /// current frame
+    pub fn is_visible(&self) -> bool {
+        let phase = (self.flickering / FLICKER_INTERVAL) as u32;
 
-        let (animation, scale) = self.get_animation_ref();
-
-        animation.draw(
-            ctx,
-            DrawParams::new()
-                .position(self.position)
-                .origin(Vec2::new(8.0, 8.0))
-                .scale(scale),
-        );
+        self.flickering == 0.0 || !phase.is_multiple_of(2)
     }
 
-    pub fn update_from_key_press(
+    /// Moves this humanoid `dt` seconds according to the given
+    /// movement input, where each axis can be -1, 0 or 1.
+    ///
+    /// `hero_speed` is how many pixels per second each unit of
+    /// built-up velocity moves the hero.
+    pub fn update_from_input(
         &mut self,
-        ctx: &mut Context,
+        movement: Vec2<f32>,
         hero_speed: f32,
+        dt: f32,
     ) {
-        // Drag is only applied to the previous frame movement
-        const HERO_MOVING_DRAG: f32 = 1.4;
-        const HERO_STOPPING_DRAG: f32 = 1.9;
-
-        let is_key_pressed_f32 =
-            |key| input::is_key_down(ctx, key) as u8 as f32;
-        // Movement for the axis x and y, can be -1, 0 or 1
-        // We assume that 1.0 - 1.0 is always perfectly 0.0
-        let x = is_key_pressed_f32(Key::D)
-            - is_key_pressed_f32(Key::A);
-        let y = is_key_pressed_f32(Key::S)
-            - is_key_pressed_f32(Key::W);
+        // Drag is only applied to the previous movement, and is
+        // the rate (per second) at which it decays.
+        // Equivalent to dividing the velocity by 1.4 and 1.9
+        // every 60th of a second (60 * ln 1.4, 60 * ln 1.9).
+        const HERO_MOVING_DRAG: f32 = 20.2;
+        const HERO_STOPPING_DRAG: f32 = 38.5;
+        // How much velocity a second of input builds up
+        const HERO_ACCELERATION: f32 = 60.0;
+        // How fast (in pixels per second) the hero always moves
+        // in the direction of the input
+        const HERO_BASE_SPEED: f32 = 60.0;
+
+        let Vec2 { x, y } = movement;
 
         // Will be added to self.velocity
         let mut new_velocity = Vec2 { x, y };
 
-        let dir = match (x as i32, y as i32) {
+        // A stick faces the hero the way the closest keys would
+        let dir = match (x.round() as i32, y.round() as i32) {
             (-1, 0) => Direction::West,
             (0, 1) => Direction::South,
             (1, 0) => Direction::East,
@@ -210,38 +243,37 @@ impl Humanoid {
 
         self.direction = dir;
 
-        // Movement is in diagonal if both x and y contain
-        // non-zero values
-        let is_diagonal = x != 0.0 && y != 0.0;
         // Moving in the diagonal shouldn't be faster than in
-        // vertical or horizontal, so we make sure that
-        // the length of this Vec2 is always 1 (x² + y² == 1)
-        // X and Y will equal to 0.707106...
+        // vertical or horizontal, so we make sure that the
+        // length of this Vec2 is never over 1 (x² + y² <= 1).
+        // With keys, X and Y will equal to 0.707106...
         //
-        // Need if because .normalize() on Vec2 {0, 0} is chaotic
-        if is_diagonal {
+        // Shorter input comes from a stick that isn't pushed all
+        // the way, and is kept as is to move slower
+        if new_velocity.magnitude_squared() > 1.0 {
             new_velocity.normalize();
         }
 
-        // Apply drag.
-        // This way of applying drag depends on the framerate,
-        // but that's not a huge problem,
-        // because currently all our movement does.
-        if new_velocity.magnitude() == 0.0 {
+        // Apply drag as an exponential decay, so that it
+        // doesn't depend on how often we're called
+        let drag = if new_velocity.magnitude() == 0.0 {
             // If no input was added, apply more drag to stop the
             // hero
-            self.velocity /= HERO_STOPPING_DRAG;
+            HERO_STOPPING_DRAG
         } else {
-            self.velocity /= HERO_MOVING_DRAG;
-        }
+            HERO_MOVING_DRAG
+        };
+        self.velocity *= f32::exp(-drag * dt);
 
         let new_pos = self.position
-            + new_velocity
-            + self.velocity * hero_speed;
+            + (new_velocity * HERO_BASE_SPEED
+                + self.velocity * hero_speed)
+                * dt;
 
         if BOUNDS.contains(new_pos) {
             self.position = new_pos;
-            self.velocity += new_velocity;
+            self.velocity +=
+                new_velocity * HERO_ACCELERATION * dt;
         }
     }
 
@@ -278,61 +310,115 @@ impl Humanoid {
         f32::atan2(delta_y, delta_x)
     }
 
-    pub fn rectangle(&self) -> Rectangle {
-        Rectangle::new(
-            self.position.x,
-            self.position.y,
-            16.0,
-            16.0,
-        )
+    /// Where this humanoid can be hit
+    pub fn collider(&self) -> Collider {
+        self.kind.hitbox().at(self.position)
     }
 
-    pub fn head_to(&mut self, is_running_fast: bool, destination: Vec2<f32>) {
+    /// Moves this humanoid `dt` seconds towards `destination`,
+    /// at its velocity (in pixels per second)
+    pub fn head_to(
+        &mut self,
+        is_running_fast: bool,
+        destination: Vec2<f32>,
+        dt: f32,
+    ) {
         let modifier = if is_running_fast { 1.5 } else { 1.0 };
         let theta_rad = self.angle_to_pos(destination);
 
         self.position +=
             Vec2::new(f32::cos(theta_rad), -f32::sin(theta_rad))
-                * self.velocity * modifier;
+                * self.velocity
+                * modifier
+                * dt;
 
         // Sets the Humanoid's Direction according to the
         // calculated angle
         self.look_to(theta_rad.to_degrees());
     }
 
-    /// Checks if the sprite of `self` collided with one of the
-    /// given `Humanoid`s.
-    pub fn collided_with_bodies(
-        &self,
-        bodies: &[Humanoid],
-    ) -> (bool, Vec<Rectangle>) {
-        let player_rect = self.rectangle();
-        let body_rects: Vec<_> = bodies
-            .iter()
-            .map(|e| e.position)
-            .map(Vec2::into_tuple)
-            .map(|(x, y)| Rectangle::new(x, y, 16.0, 16.0))
-            .collect();
-
-        for body_rect in &body_rects {
-            if player_rect.intersects(body_rect) {
-                return (true, body_rects);
-            }
-        }
-
-        (false, body_rects)
-    }
-
     pub fn is_dead(&self) -> bool {
         self.hearts == 0
     }
 
     pub fn take_hit(&mut self) {
-        if self.flickering == 0 {
+        if self.is_invincible {
+            return;
+        }
+
+        if self.flickering == 0.0 {
             if self.hearts > 0 {
                 self.hearts -= 1;
             }
-            self.flickering = 30;
+            self.flickering = self.invulnerability_time;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestep::TICK_DURATION;
+
+    const WAIT_TIME: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn shooting_waits_for_the_cooldown() {
+        let mut clock = GameClock::new();
+        let mut shooting =
+            ShootingBehavior::new(&clock, true, WAIT_TIME);
+        assert!(!shooting.can_fire(&clock));
+
+        clock.advance(WAIT_TIME);
+        assert!(shooting.can_fire(&clock));
+
+        shooting.register_fire(&clock);
+        assert!(!shooting.can_fire(&clock));
+
+        clock.advance(WAIT_TIME - Duration::from_millis(1));
+        assert!(!shooting.can_fire(&clock));
+
+        clock.advance(Duration::from_millis(1));
+        assert!(shooting.can_fire(&clock));
+    }
+
+    #[test]
+    fn shooting_cooldown_stops_while_paused() {
+        let mut clock = GameClock::new();
+        let shooting =
+            ShootingBehavior::new(&clock, true, WAIT_TIME);
+
+        clock.set_paused(true);
+        clock.advance(WAIT_TIME * 10);
+
+        assert!(!shooting.can_fire(&clock));
+    }
+
+    #[test]
+    fn shooting_cooldown_follows_the_time_scale() {
+        let mut clock = GameClock::new();
+        let shooting =
+            ShootingBehavior::new(&clock, true, WAIT_TIME);
+
+        // At double speed, the cooldown is over in half the ticks
+        clock.set_time_scale(2.0);
+        let mut ticks = 0;
+        while !shooting.can_fire(&clock) {
+            clock.advance(TICK_DURATION);
+            ticks += 1;
+        }
+
+        assert_eq!(ticks, 7);
+    }
+
+    #[test]
+    fn shooting_can_be_disallowed() {
+        let mut clock = GameClock::new();
+        let shooting =
+            ShootingBehavior::new(&clock, false, WAIT_TIME);
+
+        clock.advance(WAIT_TIME * 10);
+
+        assert!(!shooting.can_fire(&clock));
+    }
+}
@@ -0,0 +1,268 @@
+use tetra::{math::Vec2, Context};
+
+use crate::{
+    bindings::{Bindings, InputAction},
+    bot,
+    difficulty::Difficulty,
+    gamepad::Gamepads,
+    replay::{ReplayPlayer, ReplayRecorder},
+    simulation::Simulation,
+};
+
+/// How the player aims when not using a gamepad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AimMode {
+    /// Firing in 8 directions with the fire bindings, which
+    /// are the arrow keys by default
+    #[default]
+    Keys,
+    /// Firing towards the cursor while the left mouse button
+    /// is held, with the fire bindings still working
+    Mouse,
+}
+
+impl AimMode {
+    pub const ALL: [AimMode; 2] =
+        [AimMode::Keys, AimMode::Mouse];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AimMode::Keys => "Keys",
+            AimMode::Mouse => "Mouse",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// How the player controls the game
+#[derive(Debug, Clone, Default)]
+pub struct ControlSettings {
+    pub aim: AimMode,
+    pub bindings: Bindings,
+}
+
+/// Everything the player did during a single tick of the
+/// simulation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputFrame {
+    /// Movement for the axis x and y, each from -1 to 1. Keys
+    /// only ever give -1, 0 or 1, while a stick goes anywhere
+    /// in between
+    pub movement: Vec2<f32>,
+    /// The angle (in degrees) the player wants to fire
+    /// towards, if any
+    pub fire_angle: Option<f32>,
+}
+
+impl InputFrame {
+    /// Reads the sticks of the gamepad, falling back to the
+    /// bound keys and buttons for whichever of them is at rest.
+    /// Aiming with the mouse, which gives `mouse_angle`, comes
+    /// before the fire bindings
+    pub fn from_player(
+        ctx: &Context,
+        bindings: &Bindings,
+        gamepads: &Gamepads,
+        mouse_angle: Option<f32>,
+    ) -> Self {
+        let bound = Self::from_bindings(ctx, bindings, gamepads);
+
+        let movement = gamepads.movement(ctx);
+        Self {
+            movement: if movement == Vec2::zero() {
+                bound.movement
+            } else {
+                movement
+            },
+            fire_angle: gamepads
+                .fire_angle(ctx)
+                .or(mouse_angle)
+                .or(bound.fire_angle),
+        }
+    }
+
+    /// Reads the move and fire bindings, which are WASD and
+    /// the arrow keys by default
+    fn from_bindings(
+        ctx: &Context,
+        bindings: &Bindings,
+        gamepads: &Gamepads,
+    ) -> Self {
+        let is_down =
+            |action| bindings.is_down(ctx, gamepads, action);
+        let is_down_f32 = |action| is_down(action) as u8 as f32;
+
+        // We assume that 1.0 - 1.0 is always perfectly 0.0
+        let movement = Vec2 {
+            x: is_down_f32(InputAction::MoveRight)
+                - is_down_f32(InputAction::MoveLeft),
+            y: is_down_f32(InputAction::MoveDown)
+                - is_down_f32(InputAction::MoveUp),
+        };
+
+        let fire_angle = Self::check_for_fire(
+            is_down(InputAction::FireLeft),
+            is_down(InputAction::FireRight),
+            is_down(InputAction::FireUp),
+            is_down(InputAction::FireDown),
+        );
+
+        Self {
+            movement,
+            fire_angle,
+        }
+    }
+
+    // TODO: there's probably a nicer solution to this with
+    // algebra
+    fn check_for_fire(
+        left: bool,
+        right: bool,
+        up: bool,
+        down: bool,
+    ) -> Option<f32> {
+        match (left, right, up, down) {
+            // These first cases are kind of nonsensical so I'm
+            // going to explicitly ignore them
+            (true, true, _, _) => None,
+            (_, _, true, true) => None,
+            (true, false, true, false) => {
+                // Left and Up -> 135 deg
+                Some(135.0)
+            }
+            (true, false, false, true) => {
+                // Left and Down -> 225 deg
+                Some(225.0)
+            }
+            (false, true, false, true) => {
+                // Right and Down -> 315 deg
+                Some(315.0)
+            }
+            (false, true, true, false) => {
+                // Right and Up -> 45 deg
+                Some(45.0)
+            }
+            (true, false, false, false) => {
+                // Only Left -> 180 deg
+                Some(180.0)
+            }
+            (false, true, false, false) => {
+                // Only Right -> 0 deg
+                Some(0.0)
+            }
+            (false, false, true, false) => {
+                // Only Up -> 90 deg
+                Some(90.0)
+            }
+            (false, false, false, true) => {
+                // Only Down -> 270 deg
+                Some(270.0)
+            }
+            (false, false, false, false) => {
+                // No arrow buttons pressed
+                None
+            }
+        }
+    }
+}
+
+/// Where the input of each tick comes from
+pub enum InputSource {
+    /// The keyboard or a gamepad, optionally recording every
+    /// tick into a replay file
+    Keyboard(Option<ReplayRecorder>),
+    /// A replay file being played back
+    Replay(ReplayPlayer),
+}
+
+impl InputSource {
+    /// The input for the next tick, or `None` once a replay
+    /// has run out of inputs. See [`InputFrame::from_player`]
+    /// for `mouse_angle`
+    pub fn next_input(
+        &mut self,
+        ctx: &Context,
+        bindings: &Bindings,
+        gamepads: &Gamepads,
+        mouse_angle: Option<f32>,
+    ) -> Option<InputFrame> {
+        match self {
+            InputSource::Keyboard(recorder) => {
+                let input = InputFrame::from_player(
+                    ctx,
+                    bindings,
+                    gamepads,
+                    mouse_angle,
+                );
+
+                Some(record(recorder, input))
+            }
+            InputSource::Replay(player) => player.next_input(),
+        }
+    }
+
+    /// Like [`InputSource::next_input`], but with the
+    /// [bot](crate::bot) standing in for the player
+    pub fn next_bot_input(
+        &mut self,
+        sim: &Simulation,
+    ) -> Option<InputFrame> {
+        match self {
+            InputSource::Keyboard(recorder) => {
+                Some(record(recorder, bot::play(sim)))
+            }
+            InputSource::Replay(player) => player.next_input(),
+        }
+    }
+
+    /// The difficulty of the replay being recorded or played
+    /// back, if any
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        match self {
+            InputSource::Keyboard(recorder) => {
+                recorder.as_ref().map(ReplayRecorder::difficulty)
+            }
+            InputSource::Replay(player) => {
+                Some(player.difficulty())
+            }
+        }
+    }
+
+    /// Stops recording, writing out what's left of the replay
+    pub fn finish_recording(&mut self) {
+        if let InputSource::Keyboard(recorder) = self {
+            if let Some(Err(err)) =
+                recorder.take().map(ReplayRecorder::finish)
+            {
+                eprintln!(
+                    "[LOG] Failed to save the replay: {err}"
+                );
+            }
+        }
+    }
+}
+
+/// Records `input` if there's a replay being recorded, and
+/// returns it as it was recorded
+fn record(
+    recorder: &mut Option<ReplayRecorder>,
+    input: InputFrame,
+) -> InputFrame {
+    let Some(rec) = recorder else {
+        return input;
+    };
+
+    match rec.record(&input) {
+        Ok(recorded) => recorded,
+        Err(err) => {
+            eprintln!(
+                "[LOG] Stopped recording the replay: {err}"
+            );
+            *recorder = None;
+            input
+        }
+    }
+}
@@ -1,26 +1,67 @@
 mod animation;
 mod background;
+mod bindings;
+mod bot;
 mod bounds;
+mod cli;
+mod clock;
+mod command;
+mod config;
+mod console;
+mod controls;
+mod difficulty;
 mod direction;
 mod enemy;
+mod event;
+mod gameover;
+mod gamepad;
 mod gamestate;
+mod headless;
 mod healthbar;
+mod highscores;
+mod hitbox;
+mod hud;
 mod humanoid;
+mod input;
 mod macros;
+mod menu;
 mod oneoffanim;
+mod overlay;
 mod panel;
+mod paths;
+mod pause;
 mod player;
+mod playing;
 mod powerup;
 mod projectile;
+mod renderer;
+mod replay;
 mod resources;
+mod scene;
+mod scoretable;
+mod settings;
+mod simulation;
+mod sound;
+mod spatial;
 mod textures;
 mod timer;
+mod timestep;
+mod title;
 mod traits;
+mod waves;
 
 use bounds::Bounds;
+use cli::Args;
+use config::Config;
+use difficulty::Difficulty;
 use direction::Direction;
 use gamestate::GameState;
-use tetra::ContextBuilder;
+use input::InputSource;
+use playing::Playing;
+use rand::{rngs::OsRng, Rng};
+use replay::{ReplayPlayer, ReplayRecorder};
+use tetra::{time::Timestep, ContextBuilder};
+use title::Title;
 
 const WIDTH: i32 = 800;
 const HEIGHT: i32 = 800;
@@ -29,11 +70,127 @@ const BOUNDS: Bounds = Bounds::new(800.0, 800.0);
 const VERSION: &str = "0.1.0";
 
 fn main() -> tetra::Result {
+    let args = Args::parse_or_exit();
+
     println!("Endless Trial v{VERSION}");
-    ContextBuilder::new("Endless Trial", WIDTH, HEIGHT)
-        .quit_on_escape(true)
-        .debug_info(true)
-        .resizable(true)
-        .build()?
-        .run(GameState::new)
+
+    let waves = match &args.waves {
+        Some(path) => waves::load(path).unwrap_or_else(exit),
+        None => waves::default_waves(),
+    };
+
+    if let Some(wave) = args.wave {
+        if !waves.has_wave(wave) {
+            return exit(format!(
+                "there are only {} waves",
+                waves.list.len()
+            ));
+        }
+    }
+
+    if args.headless {
+        let (seed, source) = input_source(
+            &args,
+            args.difficulty.unwrap_or_default(),
+        )
+        .unwrap_or_else(exit);
+        headless::run(&args, waves, seed, source);
+
+        return Ok(());
+    }
+
+    let config = Config::load(args.config.clone());
+    let difficulty =
+        args.difficulty.unwrap_or(config.difficulty);
+
+    // Asking for a specific run skips the title screen
+    let run = args.asks_for_run().then(|| {
+        input_source(&args, difficulty).unwrap_or_else(exit)
+    });
+
+    let mut window = config.window;
+    if let Some(fullscreen) = args.fullscreen {
+        window.fullscreen = fullscreen;
+    }
+
+    ContextBuilder::new(
+        "Endless Trial",
+        window.width,
+        window.height,
+    )
+    // Escape opens the pause menu instead
+    .quit_on_escape(false)
+    .debug_info(cfg!(debug_assertions))
+    .resizable(true)
+    .fullscreen(window.fullscreen)
+    .vsync(window.vsync)
+    // `GameState` runs its own fixed timestep for the game
+    // logic, so we want to be updated once every frame
+    .timestep(Timestep::Variable)
+    .build()?
+    .run(|ctx| {
+        GameState::new(
+            ctx,
+            waves,
+            &args,
+            config,
+            |ctx, shared| match run {
+                Some((seed, input_source)) => {
+                    let playing =
+                        Playing::new(shared, seed, input_source);
+
+                    match args.wave {
+                        Some(wave) => {
+                            Box::new(playing.start_at_wave(wave))
+                        }
+                        None => Box::new(playing),
+                    }
+                }
+                None => Box::new(Title::new(ctx, shared)),
+            },
+        )
+    })
+}
+
+/// Prints a fatal error and exits
+fn exit<T>(err: String) -> T {
+    eprintln!("error: {err}");
+    std::process::exit(1);
+}
+
+/// Picks the seed of the run and where its inputs come from:
+/// either a replay being played back, or the keyboard. New
+/// replays are recorded on `difficulty`
+fn input_source(
+    args: &Args,
+    difficulty: Difficulty,
+) -> Result<(u64, InputSource), String> {
+    if let Some(path) = &args.replay {
+        let player =
+            ReplayPlayer::open(path).map_err(|err| {
+                format!(
+                    "could not read {}: {err}",
+                    path.display()
+                )
+            })?;
+
+        return Ok((player.seed(), InputSource::Replay(player)));
+    }
+
+    let seed = args.seed.unwrap_or_else(|| OsRng.gen());
+
+    let recorder = match &args.record {
+        Some(path) => Some(
+            ReplayRecorder::create(path, seed, difficulty)
+                .map_err(|err| {
+                    format!(
+                        "could not create {}: {err}",
+                        path.display()
+                    )
+                })?,
+        ),
+        None => None,
+    };
+
+    Ok((seed, InputSource::Keyboard(recorder)))
 }
@@ -0,0 +1,179 @@
+use tetra::{
+    graphics::{text::Text, Color, DrawParams},
+    input::{self, Key},
+    math::Vec2,
+    Context,
+};
+
+use crate::{panel::Panel, scene::Shared, HEIGHT, WIDTH};
+
+/// The color of the entry currently selected
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+
+/// How far apart each entry is drawn from the next
+const ENTRY_SPACING: f32 = 30.0;
+
+/// How much room is left for the title
+const TITLE_HEIGHT: f32 = 64.0;
+
+/// Empty space between the border of the panel and its text
+const PADDING: f32 = 8.0;
+
+/// A list of entries drawn over a [`Panel`], which the player
+/// can go through with the keyboard
+pub struct Menu<T> {
+    panel: Panel,
+    title: Text,
+    entries: Vec<(T, Text)>,
+    /// The index of the selected entry
+    selected: usize,
+    width: f32,
+}
+
+impl<T: Copy + PartialEq> Menu<T> {
+    pub fn new(
+        ctx: &mut Context,
+        shared: &Shared,
+        title: &str,
+        entries: &[(T, &str)],
+    ) -> Self {
+        let title = Text::new(title, shared.fonts.large.clone());
+        let entries = entries
+            .iter()
+            .map(|&(entry, label)| {
+                (
+                    entry,
+                    Text::new(label, shared.fonts.small.clone()),
+                )
+            })
+            .collect();
+
+        let mut menu = Self {
+            panel: shared.panel.clone(),
+            title,
+            entries,
+            selected: 0,
+            width: 0.0,
+        };
+        menu.fit_width(ctx);
+
+        menu
+    }
+
+    /// Fits the panel around its widest line of text
+    fn fit_width(&mut self, ctx: &mut Context) {
+        let widest_line = self
+            .entries
+            .iter_mut()
+            .map(|(_, text)| text)
+            .chain(Some(&mut self.title))
+            .filter_map(|text| text.get_bounds(ctx))
+            .map(|bounds| bounds.width)
+            .fold(0.0, f32::max);
+
+        self.width = widest_line + 2.0 * PADDING;
+    }
+
+    /// Changes the text shown for `entry`
+    pub fn set_label(
+        &mut self,
+        ctx: &mut Context,
+        entry: T,
+        label: &str,
+    ) {
+        for (_, text) in self
+            .entries
+            .iter_mut()
+            .filter(|(other, _)| *other == entry)
+        {
+            text.set_content(label);
+        }
+
+        self.fit_width(ctx);
+    }
+
+    /// The entry currently selected
+    pub fn selected(&self) -> T {
+        self.entries[self.selected].0
+    }
+
+    /// The height of the panel, which is drawn at the center
+    /// of the screen
+    pub fn height(&self) -> f32 {
+        PADDING
+            + TITLE_HEIGHT
+            + ENTRY_SPACING * self.entries.len() as f32
+            + 2.0 * PADDING
+    }
+
+    /// Moves the selection with W/S or the arrow keys, and
+    /// returns the selected entry once Enter or Space is pressed
+    pub fn update(&mut self, ctx: &Context) -> Option<T> {
+        let pressed = |keys: &[Key]| {
+            keys.iter()
+                .any(|&key| input::is_key_pressed(ctx, key))
+        };
+
+        let entries = self.entries.len();
+        if pressed(&[Key::Up, Key::W]) {
+            self.selected =
+                (self.selected + entries - 1) % entries;
+        }
+        if pressed(&[Key::Down, Key::S]) {
+            self.selected = (self.selected + 1) % entries;
+        }
+
+        if pressed(&[Key::Enter, Key::Space]) {
+            return Some(self.entries[self.selected].0);
+        }
+
+        None
+    }
+
+    /// Draws the menu at the center of the screen
+    pub fn draw(&mut self, ctx: &mut Context) {
+        let height = self.height();
+
+        let position = Vec2 {
+            x: (WIDTH as f32 - self.width) / 2.0,
+            y: (HEIGHT as f32 - height) / 2.0,
+        };
+
+        self.panel.sprite.draw_nine_slice(
+            ctx,
+            &self.panel.config,
+            self.width,
+            height,
+            position,
+        );
+
+        self.title
+            .draw(ctx, position + Vec2::new(PADDING, PADDING));
+
+        for (index, (_, text)) in
+            self.entries.iter_mut().enumerate()
+        {
+            let color = if index == self.selected {
+                SELECTED_COLOR
+            } else {
+                Color::WHITE
+            };
+
+            text.draw(
+                ctx,
+                DrawParams::new()
+                    .position(
+                        position
+                            + Vec2::new(
+                                PADDING,
+                                PADDING
+                                    + TITLE_HEIGHT
+                                    + ENTRY_SPACING
+                                        * index as f32,
+                            ),
+                    )
+                    .color(color),
+            );
+        }
+    }
+}
@@ -1,22 +1,16 @@
-use std::{
-    time::{Duration, Instant},
-    usize,
-};
-
-use tetra::{
-    graphics::{
-        animation::Animation, DrawParams, Rectangle, Texture,
-    },
-    math::Vec2,
-    Context,
-};
-
-use crate::{
-    resources::{EXPLOSION, SMOKE},
-    traits::Cleanable,
-};
-
-struct OneOffAnimation {
+use std::time::Duration;
+
+use tetra::math::Vec2;
+
+use crate::{clock::GameClock, traits::Cleanable};
+
+/// How many frames the explosion sprite sheet has
+pub const EXPLOSION_FRAMES: u8 = 10;
+
+/// How many frames the smoke sprite sheet has
+pub const SMOKE_FRAMES: u8 = 6;
+
+pub struct OneOffAnimation {
     current_frame: u8,
     position: Vec2<f32>,
 }
@@ -28,16 +22,22 @@ impl OneOffAnimation {
             current_frame: 0,
         }
     }
+
+    pub fn current_frame(&self) -> u8 {
+        self.current_frame
+    }
+
+    pub fn position(&self) -> Vec2<f32> {
+        self.position
+    }
 }
 
+/// Keeps track of explosions and smokes. All moments are in
+/// game time.
 pub struct OneOffAnimationManager {
-    last_updated_time: Instant,
-    last_smoke_added_time: Instant,
-    last_explosion_added_time: Instant,
-    explosion_anim: Animation,
-    explosion_anim_frames: u8,
-    smoke_anim: Animation,
-    smoke_anim_frames: u8,
+    last_updated_time: Duration,
+    last_smoke_added_time: Duration,
+    last_explosion_added_time: Duration,
     explosions: Vec<OneOffAnimation>,
     smokes: Vec<OneOffAnimation>,
 }
@@ -45,9 +45,8 @@ pub struct OneOffAnimationManager {
 impl Cleanable for OneOffAnimationManager {
     /// Remove animations that have finished
     fn clean_up(&mut self) {
-        let explosion_final_frame =
-            self.explosion_anim_frames - 1;
-        let smoke_final_frame = self.smoke_anim_frames - 1;
+        let explosion_final_frame = EXPLOSION_FRAMES - 1;
+        let smoke_final_frame = SMOKE_FRAMES - 1;
 
         self.explosions.retain(|x| {
             x.current_frame != explosion_final_frame
@@ -59,99 +58,77 @@ impl Cleanable for OneOffAnimationManager {
 }
 
 impl OneOffAnimationManager {
-    pub fn new(ctx: &mut Context) -> Self {
-        let now = Instant::now();
-
-        let explosion_sprite = Texture::from_encoded(
-            ctx, EXPLOSION,
-        )
-        .expect("Failed to load built-in explosion sprite");
-
-        let smoke_sprite = Texture::from_encoded(ctx, SMOKE)
-            .expect("Failed to load built-in smoke sprite");
-
-        let explosion_anim = Animation::new(
-            explosion_sprite,
-            Rectangle::row(0.0, 0.0, 64.0, 64.0)
-                .take(10)
-                .collect(),
-            Duration::from_secs_f32(0.05),
-        );
-
-        let explosion_anim_frames =
-            explosion_anim.frames().len() as u8;
-
-        let smoke_anim = Animation::new(
-            smoke_sprite,
-            Rectangle::row(0.0, 0.0, 64.0, 64.0)
-                .take(6)
-                .collect(),
-            Duration::from_secs_f32(0.05),
-        );
-
-        let smoke_anim_frames = smoke_anim.frames().len() as u8;
-
-        let mgr = Self {
-            last_updated_time: Instant::now(),
-            last_explosion_added_time: Instant::now(),
-            last_smoke_added_time: Instant::now(),
-            explosion_anim,
-            explosion_anim_frames,
-            smoke_anim,
-            smoke_anim_frames,
+    pub fn new(clock: &GameClock) -> Self {
+        Self {
+            last_updated_time: clock.now(),
+            last_explosion_added_time: clock.now(),
+            last_smoke_added_time: clock.now(),
             explosions: Vec::with_capacity(12),
             smokes: Vec::with_capacity(12),
-        };
+        }
+    }
 
-        println!(
-            "Built OneOffAnimationManager in {}ms",
-            now.elapsed().as_millis()
-        );
+    pub fn explosions(&self) -> &[OneOffAnimation] {
+        &self.explosions
+    }
 
-        mgr
+    pub fn smokes(&self) -> &[OneOffAnimation] {
+        &self.smokes
     }
 
-    pub fn add_explosion(&mut self, position: Vec2<f32>) {
-        if !self.can_add_explosion() {
+    pub fn add_explosion(
+        &mut self,
+        position: Vec2<f32>,
+        clock: &GameClock,
+    ) {
+        if !self.can_add_explosion(clock) {
             return;
         }
-        self.last_explosion_added_time = Instant::now();
+        self.last_explosion_added_time = clock.now();
         let explosion_anim = OneOffAnimation::new(position);
         self.explosions.push(explosion_anim);
     }
 
-    fn can_add_smoke(&self) -> bool {
-        let elapsed = self.last_smoke_added_time.elapsed();
+    fn can_add_smoke(&self, clock: &GameClock) -> bool {
+        let elapsed = clock
+            .now()
+            .saturating_sub(self.last_smoke_added_time);
 
         elapsed > Duration::from_secs_f32(0.2)
     }
 
-    fn can_add_explosion(&self) -> bool {
-        let elapsed = self.last_explosion_added_time.elapsed();
+    fn can_add_explosion(&self, clock: &GameClock) -> bool {
+        let elapsed = clock
+            .now()
+            .saturating_sub(self.last_explosion_added_time);
 
         elapsed > Duration::from_secs_f32(0.2)
     }
 
-    pub fn add_smoke(&mut self, position: Vec2<f32>) {
-        if !self.can_add_smoke() {
+    pub fn add_smoke(
+        &mut self,
+        position: Vec2<f32>,
+        clock: &GameClock,
+    ) {
+        if !self.can_add_smoke(clock) {
             // Avoid spawning many smoke animations in the same
             // place (e.g. if the fireball hits the
             // enemy but he doesn't die)
             return;
         }
-        self.last_smoke_added_time = Instant::now();
+        self.last_smoke_added_time = clock.now();
         let smoke_anim = OneOffAnimation::new(position);
         self.smokes.push(smoke_anim);
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self, clock: &GameClock) {
         self.clean_up();
 
-        if !self.can_update_frames() {
+        if !self.can_update_frames(clock) {
             return;
         }
 
-        self.last_updated_time = Instant::now();
+        self.last_updated_time = clock.now();
 
         for explosion in &mut self.explosions {
             explosion.current_frame += 1;
@@ -162,35 +139,10 @@ impl OneOffAnimationManager {
         }
     }
 
-    fn can_update_frames(&self) -> bool {
-        let elapsed = self.last_updated_time.elapsed();
+    fn can_update_frames(&self, clock: &GameClock) -> bool {
+        let elapsed =
+            clock.now().saturating_sub(self.last_updated_time);
 
         elapsed > Duration::from_secs_f32(0.10)
     }
-
-    pub fn draw(&mut self, ctx: &mut Context) {
-        for explosion in &self.explosions {
-            self.explosion_anim.set_current_frame_index(
-                explosion.current_frame as usize,
-            );
-            self.explosion_anim.draw(
-                ctx,
-                DrawParams::new()
-                    .position(explosion.position)
-                    .origin(Vec2::new(16.0, 16.0)),
-            );
-        }
-
-        for smoke in &self.smokes {
-            self.smoke_anim.set_current_frame_index(
-                smoke.current_frame as usize,
-            );
-            self.smoke_anim.draw(
-                ctx,
-                DrawParams::new()
-                    .position(smoke.position)
-                    .origin(Vec2::new(16.0, 16.0)),
-            );
-        }
-    }
 }
@@ -0,0 +1,433 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use tetra::{
+    graphics::{
+        mesh::{GeometryBuilder, ShapeStyle},
+        text::{Font, Text},
+        Color, DrawParams, Rectangle,
+    },
+    math::Vec2,
+    Context,
+};
+
+use crate::{
+    hitbox::Collider, projectile::Projectile,
+    simulation::Simulation, HEIGHT, WIDTH,
+};
+
+const PLAYER_COLOR: Color = Color::rgb(0.2, 1.0, 0.2);
+const ENEMY_COLOR: Color = Color::rgb(1.0, 0.2, 0.2);
+const TARGET_LINE_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.3);
+const FIREBALL_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+const CANNONBALL_COLOR: Color = Color::rgb(1.0, 0.4, 1.0);
+const POWER_UP_COLOR: Color = Color::rgb(0.3, 0.7, 1.0);
+const UPDATE_COLOR: Color = Color::rgb(0.3, 0.9, 1.0);
+const DRAW_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+const BUDGET_LINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+const BACKGROUND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+/// Projectiles get a line to where they'll be this many seconds
+/// from now
+const VELOCITY_LOOKAHEAD: f32 = 0.15;
+
+/// How many frames the frame-time graph goes back
+const FRAME_HISTORY: usize = 120;
+/// How wide each frame is in the graph, in pixels
+const GRAPH_STEP: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 80.0;
+/// The frame time at the top of the graph. Longer frames are
+/// cut off there
+const GRAPH_MAX: Duration = Duration::from_micros(33_333);
+/// A frame at 60 FPS, marked across the graph
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+const MARGIN: f32 = 16.0;
+const PADDING: f32 = 6.0;
+
+/// The parts of a frame that are timed separately
+#[derive(Clone, Copy)]
+pub enum FrameStage {
+    Update,
+    Draw,
+}
+
+/// How long the last few updates and draws took
+struct FrameTimes {
+    update: VecDeque<Duration>,
+    draw: VecDeque<Duration>,
+    /// When the stage being timed started
+    started: Instant,
+}
+
+impl FrameTimes {
+    fn new() -> Self {
+        Self {
+            update: VecDeque::with_capacity(FRAME_HISTORY),
+            draw: VecDeque::with_capacity(FRAME_HISTORY),
+            started: Instant::now(),
+        }
+    }
+
+    fn history(&self, stage: FrameStage) -> &VecDeque<Duration> {
+        match stage {
+            FrameStage::Update => &self.update,
+            FrameStage::Draw => &self.draw,
+        }
+    }
+
+    fn record(&mut self, stage: FrameStage, elapsed: Duration) {
+        let history = match stage {
+            FrameStage::Update => &mut self.update,
+            FrameStage::Draw => &mut self.draw,
+        };
+
+        if history.len() == FRAME_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(elapsed);
+    }
+
+    /// The average time of a stage over the whole history
+    fn average(&self, stage: FrameStage) -> Duration {
+        let history = self.history(stage);
+        if history.is_empty() {
+            return Duration::ZERO;
+        }
+
+        history.iter().sum::<Duration>() / history.len() as u32
+    }
+}
+
+/// Draws what's normally invisible about the game on top of it:
+/// the hitbox of every entity, where projectiles are going and
+/// who enemies are after, how many entities there are and how
+/// long frames take
+pub struct DebugOverlay {
+    visible: bool,
+    frame_times: FrameTimes,
+    counts_text: Text,
+    update_text: Text,
+    draw_text: Text,
+}
+
+impl DebugOverlay {
+    pub fn new(font: &Font) -> Self {
+        Self {
+            visible: false,
+            frame_times: FrameTimes::new(),
+            counts_text: Text::new("", font.clone()),
+            update_text: Text::new("", font.clone()),
+            draw_text: Text::new("", font.clone()),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Starts timing a stage of the current frame. Frames are
+    /// timed even while the overlay is hidden, so that the
+    /// graph is already filled once it's shown
+    pub fn start_timing(&mut self) {
+        self.frame_times.started = Instant::now();
+    }
+
+    pub fn finish_timing(&mut self, stage: FrameStage) {
+        let elapsed = self.frame_times.started.elapsed();
+        self.frame_times.record(stage, elapsed);
+    }
+
+    /// Draws the overlay over `sim`, the run currently on
+    /// screen. Without one, only the frame times are shown
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        sim: Option<&Simulation>,
+    ) -> tetra::Result {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let mut builder = GeometryBuilder::new();
+        let mut counts_position = None;
+
+        if let Some(sim) = sim {
+            add_run(&mut builder, sim)?;
+
+            self.counts_text.set_content(counts(sim));
+            if let Some(bounds) =
+                self.counts_text.get_bounds(ctx)
+            {
+                let background = Rectangle::new(
+                    MARGIN,
+                    HEIGHT as f32
+                        - MARGIN
+                        - bounds.height
+                        - 2.0 * PADDING,
+                    bounds.width + 2.0 * PADDING,
+                    bounds.height + 2.0 * PADDING,
+                );
+
+                builder.set_color(BACKGROUND_COLOR);
+                builder
+                    .rectangle(ShapeStyle::Fill, background)?;
+                counts_position = Some(
+                    Vec2::new(background.x, background.y)
+                        + Vec2::new(PADDING, PADDING),
+                );
+            }
+        }
+
+        let graph_position = self.add_graph(&mut builder)?;
+        builder.build_mesh(ctx)?.draw(ctx, DrawParams::new());
+
+        if let Some(position) = counts_position {
+            self.counts_text.draw(ctx, position);
+        }
+        self.draw_graph_labels(ctx, graph_position);
+
+        Ok(())
+    }
+
+    /// Adds the frame-time graph to the bottom right corner,
+    /// returning its top left corner
+    fn add_graph(
+        &self,
+        builder: &mut GeometryBuilder,
+    ) -> tetra::Result<Vec2<f32>> {
+        let width = FRAME_HISTORY as f32 * GRAPH_STEP;
+        let origin = Vec2::new(
+            WIDTH as f32 - MARGIN - width,
+            HEIGHT as f32 - MARGIN - GRAPH_HEIGHT,
+        );
+
+        // How high up the graph a frame time goes
+        let y = |time: Duration| {
+            let fraction = (time.as_secs_f32()
+                / GRAPH_MAX.as_secs_f32())
+            .min(1.0);
+
+            origin.y + GRAPH_HEIGHT * (1.0 - fraction)
+        };
+
+        builder.set_color(BACKGROUND_COLOR);
+        builder.rectangle(
+            ShapeStyle::Fill,
+            Rectangle::new(
+                origin.x,
+                origin.y,
+                width,
+                GRAPH_HEIGHT,
+            ),
+        )?;
+
+        builder.set_color(BUDGET_LINE_COLOR);
+        builder.polyline(
+            1.0,
+            &[
+                Vec2::new(origin.x, y(FRAME_BUDGET)),
+                Vec2::new(origin.x + width, y(FRAME_BUDGET)),
+            ],
+        )?;
+
+        for (stage, color) in [
+            (FrameStage::Update, UPDATE_COLOR),
+            (FrameStage::Draw, DRAW_COLOR),
+        ] {
+            let history = self.frame_times.history(stage);
+            if history.len() < 2 {
+                continue;
+            }
+
+            // The latest frame is always at the right edge
+            let first = FRAME_HISTORY - history.len();
+            let points: Vec<_> = history
+                .iter()
+                .enumerate()
+                .map(|(frame, &time)| {
+                    Vec2::new(
+                        origin.x
+                            + (first + frame) as f32
+                                * GRAPH_STEP,
+                        y(time),
+                    )
+                })
+                .collect();
+
+            builder.set_color(color);
+            builder.polyline(1.0, &points)?;
+        }
+
+        Ok(origin)
+    }
+
+    /// Labels the graph with the average time of each stage, in
+    /// the color of its line
+    fn draw_graph_labels(
+        &mut self,
+        ctx: &mut Context,
+        graph_position: Vec2<f32>,
+    ) {
+        self.update_text.set_content(format!(
+            "update {:.2}ms",
+            millis(self.frame_times.average(FrameStage::Update))
+        ));
+        self.draw_text.set_content(format!(
+            "draw {:.2}ms",
+            millis(self.frame_times.average(FrameStage::Draw))
+        ));
+
+        let Some(bounds) = self.update_text.get_bounds(ctx)
+        else {
+            return;
+        };
+        let position = graph_position
+            - Vec2::new(0.0, bounds.height + PADDING);
+
+        self.update_text.draw(
+            ctx,
+            DrawParams::new()
+                .position(position)
+                .color(UPDATE_COLOR),
+        );
+        self.draw_text.draw(
+            ctx,
+            DrawParams::new()
+                .position(
+                    position
+                        + Vec2::new(
+                            bounds.width + 2.0 * PADDING,
+                            0.0,
+                        ),
+                )
+                .color(DRAW_COLOR),
+        );
+    }
+}
+
+/// Adds the hitboxes, velocities and target lines of a run
+fn add_run(
+    builder: &mut GeometryBuilder,
+    sim: &Simulation,
+) -> tetra::Result {
+    let player_manager = sim.player_manager();
+    let enemy_mgr = sim.enemy_mgr();
+    let player_position = player_manager.player_position();
+
+    // Every enemy walks and shoots towards the player
+    builder.set_color(TARGET_LINE_COLOR);
+    for enemy in &enemy_mgr.enemies {
+        builder
+            .polyline(1.0, &[enemy.position, player_position])?;
+    }
+
+    add_colliders(
+        builder,
+        PLAYER_COLOR,
+        [player_manager.player().collider()],
+    )?;
+    add_colliders(
+        builder,
+        ENEMY_COLOR,
+        enemy_mgr.enemies.iter().map(|enemy| enemy.collider()),
+    )?;
+    add_colliders(
+        builder,
+        FIREBALL_COLOR,
+        player_manager.fireball_colliders(),
+    )?;
+    add_velocities(
+        builder,
+        FIREBALL_COLOR,
+        player_manager.fireballs(),
+    )?;
+    add_colliders(
+        builder,
+        CANNONBALL_COLOR,
+        enemy_mgr.cannonball_colliders(),
+    )?;
+    add_velocities(
+        builder,
+        CANNONBALL_COLOR,
+        enemy_mgr.cannonballs(),
+    )?;
+    add_colliders(
+        builder,
+        POWER_UP_COLOR,
+        sim.power_up_mgr()
+            .power_ups()
+            .iter()
+            .map(|power_up| power_up.collider()),
+    )?;
+
+    Ok(())
+}
+
+/// Outlines every collider in the given color
+fn add_colliders(
+    builder: &mut GeometryBuilder,
+    color: Color,
+    colliders: impl IntoIterator<Item = Collider>,
+) -> tetra::Result {
+    builder.set_color(color);
+
+    for collider in colliders {
+        match collider {
+            Collider::Aabb(rect) => builder
+                .rectangle(ShapeStyle::Stroke(1.0), rect)?,
+            Collider::Circle { center, radius } => builder
+                .circle(
+                    ShapeStyle::Stroke(1.0),
+                    center,
+                    radius,
+                )?,
+        };
+    }
+
+    Ok(())
+}
+
+/// Draws a line from every projectile to where it's headed
+fn add_velocities(
+    builder: &mut GeometryBuilder,
+    color: Color,
+    projectiles: &[Projectile],
+) -> tetra::Result {
+    builder.set_color(color);
+
+    for projectile in projectiles {
+        let position = projectile.position();
+        builder.polyline(
+            1.0,
+            &[
+                position,
+                position
+                    + projectile.velocity() * VELOCITY_LOOKAHEAD,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// How many of each entity the run has
+fn counts(sim: &Simulation) -> String {
+    let one_off_anim_mgr = sim.one_off_anim_mgr();
+
+    format!(
+        "Enemies: {}\nFireballs: {}\nCannonballs: {}\nPower-ups: {}\nAnimations: {}",
+        sim.enemy_mgr().enemies.len(),
+        sim.player_manager().fireballs().len(),
+        sim.enemy_mgr().cannonballs().len(),
+        sim.power_up_mgr().power_ups().len(),
+        one_off_anim_mgr.explosions().len()
+            + one_off_anim_mgr.smokes().len(),
+    )
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
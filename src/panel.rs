@@ -9,6 +9,7 @@ use tetra::{
 
 use crate::resources;
 
+#[derive(Clone)]
 pub struct Panel {
     pub sprite: Texture,
     pub config: NineSlice,
@@ -33,13 +34,23 @@ impl Panel {
 pub struct GameOverPanel {
     panel: Panel,
     text: Text,
+    /// The title currently in `text`
+    title: &'static str,
+    /// Shows the seed of the run, so that it can be reproduced
+    seed_text: Text,
+    /// The seed currently in `seed_text`
+    seed: Option<u64>,
+    /// Tells the player how to carry on
+    hint_text: Text,
+    /// The hint currently in `hint_text`
+    hint: String,
 }
 
 impl GameOverPanel {
     pub fn new(ctx: &mut Context) -> Self {
         let panel = Panel::new(ctx);
         let text = Text::new(
-            "Game over!",
+            "",
             Font::from_vector_file_data(
                 ctx,
                 resources::BITPOTION_FONT,
@@ -47,25 +58,78 @@ impl GameOverPanel {
             )
             .expect("Failed to instantiate font"),
         );
+        let small_font = Font::from_vector_file_data(
+            ctx,
+            resources::BITPOTION_FONT,
+            32.0,
+        )
+        .expect("Failed to instantiate font");
+        let seed_text = Text::new("", small_font.clone());
+        let hint_text = Text::new("", small_font);
 
-        Self { panel, text }
+        Self {
+            panel,
+            text,
+            title: "",
+            seed_text,
+            seed: None,
+            hint_text,
+            hint: String::new(),
+        }
     }
 
-    pub fn draw(&mut self, ctx: &mut Context) {
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        title: &'static str,
+        seed: u64,
+        hint: &str,
+    ) {
+        if self.title != title {
+            self.title = title;
+            self.text.set_content(title);
+        }
+
+        if self.seed != Some(seed) {
+            self.seed = Some(seed);
+            self.seed_text.set_content(format!("Seed: {seed}"));
+        }
+
+        if self.hint != hint {
+            self.hint = hint.to_owned();
+            self.hint_text.set_content(hint);
+        }
+
         self.panel.sprite.draw_nine_slice(
             ctx,
             &self.panel.config,
-            206.0,
-            80.,
-            Vec2 { x: 320.0, y: 320.0 },
+            330.0,
+            144.,
+            Vec2 { x: 235.0, y: 320.0 },
         );
 
         self.text.draw(
             ctx,
             Vec2 {
-                x: 320.0 + 8.0,
+                x: 235.0 + 8.0,
                 y: 320.0 + 8.0,
             },
         );
+
+        self.seed_text.draw(
+            ctx,
+            Vec2 {
+                x: 235.0 + 8.0,
+                y: 320.0 + 76.0,
+            },
+        );
+
+        self.hint_text.draw(
+            ctx,
+            Vec2 {
+                x: 235.0 + 8.0,
+                y: 320.0 + 106.0,
+            },
+        );
     }
 }
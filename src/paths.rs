@@ -0,0 +1,144 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The name of the folder our files are kept in
+const APP_FOLDER: &str = "endless-trial";
+
+/// Where the game keeps the files it writes, such as the high
+/// score table, following the conventions of each platform
+/// (see [`dirs::data_dir`]).
+///
+/// Returns `None` if the environment doesn't tell us where
+/// the user's home is.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|base| base.join(APP_FOLDER))
+}
+
+/// Writes `contents` to a temporary file next to `path` first,
+/// so that a crash halfway through can't damage the file that
+/// was there before
+pub fn write_atomically(
+    path: &Path,
+    contents: &str,
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temporary = with_suffix(path, ".tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, path)
+}
+
+/// Renames a file we can't read to `<path>.bak`, so that it
+/// isn't overwritten the next time we save over it. Returns
+/// where it was moved to
+pub fn move_aside(path: &Path) -> PathBuf {
+    let backup = with_suffix(path, ".bak");
+    if let Err(err) = fs::rename(path, &backup) {
+        eprintln!(
+            "[LOG] Failed to move {} to {}: {err}",
+            path.display(),
+            backup.display()
+        );
+    }
+
+    backup
+}
+
+/// `path` with `suffix` added after its extension
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+
+    path.into()
+}
+
+/// A directory of its own in the temporary directory, so that
+/// tests running at once don't step on each other. It's removed
+/// with everything in it once dropped
+#[cfg(test)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+#[cfg(test)]
+impl TempDir {
+    /// `name` has to be different for every test
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "endless-trial-{}-{name}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&path).unwrap();
+
+        Self { path }
+    }
+
+    pub fn join(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+
+    /// A file in the directory, holding `contents`
+    pub fn file(
+        &self,
+        file_name: &str,
+        contents: &str,
+    ) -> PathBuf {
+        let path = self.join(file_name);
+        fs::write(&path, contents).unwrap();
+
+        path
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_replace_the_whole_file() {
+        let dir = TempDir::new("paths-write");
+        let path = dir.file("settings.toml", "old contents");
+
+        write_atomically(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!dir.join("settings.toml.tmp").exists());
+    }
+
+    #[test]
+    fn unreadable_files_are_kept_as_backups() {
+        let dir = TempDir::new("paths-backup");
+        let path = dir.file("scores.txt", "garbage");
+
+        let backup = move_aside(&path);
+
+        assert_eq!(backup, dir.join("scores.txt.bak"));
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(backup).unwrap(),
+            "garbage"
+        );
+    }
+
+    #[test]
+    fn temporary_directories_are_removed() {
+        let dir = TempDir::new("paths-temp");
+        let path = dir.file("file", "contents");
+        drop(dir);
+
+        assert!(!path.exists());
+        assert!(!path.parent().unwrap().exists());
+    }
+}
@@ -0,0 +1,98 @@
+use rand::{rngs::OsRng, Rng};
+use tetra::{
+    input::{self, Key},
+    Context,
+};
+
+use crate::{
+    bindings::InputAction,
+    input::InputSource,
+    menu::Menu,
+    playing::Playing,
+    scene::{Scene, Shared, Transition},
+};
+
+/// The entries of the pause menu
+#[derive(Clone, Copy, PartialEq)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Quit,
+}
+
+/// The pause menu, drawn over the frozen run
+pub struct Paused {
+    menu: Menu<PauseAction>,
+}
+
+impl Paused {
+    pub fn new(ctx: &mut Context, shared: &Shared) -> Self {
+        Self {
+            menu: Menu::new(
+                ctx,
+                shared,
+                "Paused",
+                &[
+                    (PauseAction::Resume, "Resume"),
+                    (PauseAction::Restart, "Restart"),
+                    (PauseAction::Quit, "Quit"),
+                ],
+            ),
+        }
+    }
+}
+
+impl Scene for Paused {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        // Escape always goes back, like in every other menu
+        if input::is_key_pressed(ctx, Key::Escape)
+            || shared.config.controls.bindings.is_pressed(
+                ctx,
+                &shared.gamepads,
+                InputAction::Pause,
+            )
+        {
+            return Ok(Transition::Pop);
+        }
+
+        let transition = match self.menu.update(ctx) {
+            Some(PauseAction::Resume) => Transition::Pop,
+            // A recording or a replay only covers the run it
+            // was started with, so the new run is played from
+            // the keyboard
+            Some(PauseAction::Restart) => {
+                Transition::Switch(Box::new(Playing::new(
+                    shared,
+                    OsRng.gen(),
+                    InputSource::Keyboard(None),
+                )))
+            }
+            Some(PauseAction::Quit) => Transition::Quit,
+            None => Transition::None,
+        };
+
+        Ok(transition)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        _shared: &mut Shared,
+    ) -> tetra::Result {
+        self.menu.draw(ctx);
+
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn ducks_music(&self) -> bool {
+        true
+    }
+}
@@ -1,16 +1,17 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use tetra::{graphics::Texture, math::Vec2, Context};
+use tetra::math::Vec2;
 
 use crate::{
-    animation::FireballAnimation,
-    down,
-    humanoid::{Humanoid, HumanoidType},
-    left,
-    projectile::{Projectile, ProjectileManager},
-    resources, right,
-    traits::Cleanable,
-    up,
+    clock::GameClock,
+    difficulty::Difficulty,
+    event::GameEvent,
+    hitbox::Collider,
+    humanoid::{Humanoid, HumanoidType, ShootingBehavior},
+    input::InputFrame,
+    projectile::{
+        Projectile, ProjectileManager, FIREBALL_HITBOX,
+    },
 };
 
 pub struct PlayerManager {
@@ -35,6 +36,10 @@ impl PlayerManager {
         self.player.take_hit()
     }
 
+    pub fn player(&self) -> &Humanoid {
+        &self.player
+    }
+
     pub fn player_mut(&mut self) -> &mut Humanoid {
         &mut self.player
     }
@@ -43,13 +48,30 @@ impl PlayerManager {
         self.fireball_mgr.projectiles()
     }
 
-    pub fn update(&mut self, ctx: &mut Context) {
-        self.player.clean_up();
+    pub fn fireball_colliders(
+        &self,
+    ) -> impl Iterator<Item = Collider> + '_ {
+        self.fireball_mgr.colliders()
+    }
+
+    /// Advances the player and their fireballs up to the
+    /// current game time
+    pub fn update(
+        &mut self,
+        input: &InputFrame,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let dt = clock.delta_secs();
+
+        self.player.clean_up(clock);
+        self.player.advance_animation(clock);
+        self.fireball_mgr.advance(dt);
 
         let (
             faster_shooting_active,
-            triple_shooting_active,
             faster_running_active,
+            triple_shooting_active,
         ) = self.player.power_ups.currently_active();
 
         let wait_time = if faster_shooting_active {
@@ -62,109 +84,102 @@ impl PlayerManager {
             .shooting_behavior
             .set_shooting_wait_time(wait_time);
 
-        if self.player.can_fire() {
-            if let Some(angle) = Self::check_for_fire(ctx) {
+        if self.player.can_fire(clock) {
+            if let Some(angle) = input.fire_angle {
                 self.fireball_mgr.shoot(
                     triple_shooting_active,
                     angle,
                     self.player.position,
-                    Vec2 { x: 5.5, y: 5.5 },
+                    Vec2 { x: 330.0, y: 330.0 },
                 );
 
-                self.player.shooting_behavior.register_fire();
+                self.player
+                    .shooting_behavior
+                    .register_fire(clock);
+                events.push(GameEvent::PlayerFired);
             }
         }
 
         let hero_speed =
-            if faster_running_active { 4.5 } else { 2.1 };
+            if faster_running_active { 270.0 } else { 126.0 };
 
-        // Checks for WASD presses and updates player location
-        self.player.update_from_key_press(ctx, hero_speed);
+        // Updates player location from the WASD movement
+        self.player.update_from_input(
+            input.movement,
+            hero_speed,
+            dt,
+        );
     }
 
-    pub fn new(ctx: &mut Context) -> Self {
-        let now = Instant::now();
-
-        let player_texture =
-            Texture::from_encoded(ctx, resources::HERO).unwrap();
-
-        let fireball_animation = FireballAnimation::build(ctx);
-
-        let player_mgr = Self {
-            player: Humanoid::new(
-                2,
-                player_texture,
-                Vec2::new(240.0, 160.0),
-                Vec2::new(0.0, 0.0),
+    pub fn new(
+        clock: &GameClock,
+        difficulty: Difficulty,
+    ) -> Self {
+        let mut player = Humanoid::new(
+            clock,
+            difficulty.player_hearts(),
+            0,
+            Vec2::new(240.0, 160.0),
+            Vec2::new(0.0, 0.0),
+            ShootingBehavior::new(
+                clock,
                 true,
                 Duration::from_secs_f32(0.25),
-                HumanoidType::Player,
             ),
-            fireball_mgr: ProjectileManager::new(
-                fireball_animation,
-            ),
-        };
-
-        println!(
-            "Built PlayerManager in {}ms",
-            now.elapsed().as_millis()
+            HumanoidType::Player,
         );
+        player.invulnerability_time =
+            difficulty.invulnerability_time();
 
-        player_mgr
+        Self {
+            player,
+            fireball_mgr: ProjectileManager::new(
+                FIREBALL_HITBOX,
+            ),
+        }
     }
+}
 
-    // TODO: there's probably a nicer solution to this with
-    // algebra
-    pub fn check_for_fire(ctx: &mut Context) -> Option<f32> {
-        match (left!(ctx), right!(ctx), up!(ctx), down!(ctx)) {
-            // These first cases are kind of nonsensical so I'm
-            // going to explicitly ignore them
-            (true, true, _, _) => None,
-            (_, _, true, true) => None,
-            (true, false, true, false) => {
-                // Left and Up -> 135 deg
-                Some(135.0)
-            }
-            (true, false, false, true) => {
-                // Left and Down -> 225 deg
-                Some(225.0)
-            }
-            (false, true, false, true) => {
-                // Right and Down -> 315 deg
-                Some(315.0)
-            }
-            (false, true, true, false) => {
-                // Right and Up -> 45 deg
-                Some(45.0)
-            }
-            (true, false, false, false) => {
-                // Only Left -> 180 deg
-                Some(180.0)
-            }
-            (false, true, false, false) => {
-                // Only Right -> 0 deg
-                Some(0.0)
-            }
-            (false, false, true, false) => {
-                // Only Up -> 90 deg
-                Some(90.0)
-            }
-            (false, false, false, true) => {
-                // Only Down -> 270 deg
-                Some(270.0)
-            }
-            (false, false, false, false) => {
-                // No arrow buttons pressed
-                None
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::powerup::PowerUpKind;
+
+    fn fire_once(power_up: Option<PowerUpKind>) -> usize {
+        let mut clock = GameClock::new();
+        let mut player_mgr =
+            PlayerManager::new(&clock, Difficulty::Normal);
+        if let Some(kind) = power_up {
+            player_mgr
+                .player_mut()
+                .power_ups
+                .activate_power_up(kind, &clock);
         }
-    }
 
-    pub fn draw(&mut self, ctx: &mut Context) {
-        self.player.advance_animation(ctx);
-        self.fireball_mgr.advance_animation(ctx);
+        clock.advance(Duration::from_secs(1));
+        let input = InputFrame {
+            movement: Vec2::zero(),
+            fire_angle: Some(0.0),
+        };
+        player_mgr.update(&input, &clock, &mut Vec::new());
+
+        player_mgr.fireballs().len()
+    }
 
-        self.player.draw(ctx);
-        self.fireball_mgr.draw(ctx);
+    #[test]
+    fn only_triple_shooting_fires_three_fireballs() {
+        assert_eq!(fire_once(None), 1);
+        assert_eq!(
+            fire_once(Some(PowerUpKind::TripleShooting)),
+            3
+        );
+        assert_eq!(
+            fire_once(Some(PowerUpKind::FasterRunning)),
+            1
+        );
+        assert_eq!(
+            fire_once(Some(PowerUpKind::FasterShooting)),
+            1
+        );
     }
 }
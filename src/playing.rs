@@ -0,0 +1,387 @@
+use std::time::Duration;
+
+use tetra::{
+    input::{self, Key, MouseButton},
+    time, window, Context, Event,
+};
+
+use crate::{
+    bindings::{InputAction, CONSOLE_KEY},
+    command::Command,
+    console::Console,
+    gameover::GameOver,
+    hud::Hud,
+    input::{AimMode, InputSource},
+    pause::Paused,
+    scene::{Scene, Shared, Transition},
+    scoretable::HighScore,
+    simulation::Simulation,
+    timer::Timer,
+    timestep::FixedTimestep,
+};
+
+/// A run being played, either from the keyboard or from a
+/// replay
+pub struct Playing {
+    /// The game logic, advanced in fixed-size ticks
+    simulation: Simulation,
+    /// Decides how many ticks to run on each update
+    timestep: FixedTimestep,
+    /// Where the input of every tick comes from
+    input_source: InputSource,
+    hud: Hud,
+    /// Only there if the console is enabled
+    console: Option<Console>,
+    /// Whether the player aims at the cursor, which is never
+    /// the case in a replay
+    mouse_aim: bool,
+    /// Set once a console command changed the run, which then
+    /// makes it into neither the high scores nor a replay
+    was_changed: bool,
+    window_title_update_timer: Timer,
+}
+
+impl Playing {
+    pub fn new(
+        shared: &Shared,
+        seed: u64,
+        input_source: InputSource,
+    ) -> Self {
+        // Replays are played back on the difficulty they were
+        // recorded on
+        let difficulty = input_source
+            .difficulty()
+            .unwrap_or(shared.difficulty);
+
+        println!(
+            "Starting a run with seed {seed} on {}",
+            difficulty.name()
+        );
+
+        let mouse_aim = shared.config.controls.aim
+            == AimMode::Mouse
+            && matches!(input_source, InputSource::Keyboard(_));
+
+        Self {
+            simulation: Simulation::new(
+                seed,
+                shared.waves.clone(),
+                difficulty,
+            ),
+            timestep: FixedTimestep::new(),
+            input_source,
+            hud: Hud::new(shared),
+            console: shared
+                .console_enabled
+                .then(|| Console::new(shared)),
+            mouse_aim,
+            was_changed: false,
+            window_title_update_timer:
+                Timer::start_now_with_interval(
+                    &shared.ui_clock,
+                    Duration::from_secs(1),
+                ),
+        }
+    }
+
+    /// Starts the run at the wave at `index` instead of the
+    /// first one. Like a run changed from the console, it
+    /// doesn't make it into the high scores
+    pub fn start_at_wave(mut self, index: u16) -> Self {
+        println!("Skipping ahead to wave {}", index + 1);
+        self.simulation.jump_to_wave(index);
+        self.was_changed = true;
+
+        self
+    }
+
+    /// Freezes the game clock, and with it every timer and
+    /// animation, while the pause menu is open
+    fn pause(
+        &mut self,
+        ctx: &mut Context,
+        shared: &Shared,
+    ) -> Transition {
+        self.simulation.clock_mut().set_paused(true);
+
+        if let Some(console) = &mut self.console {
+            if console.is_open() {
+                console.toggle(ctx);
+            }
+        }
+
+        Transition::Push(Box::new(Paused::new(ctx, shared)))
+    }
+
+    /// Opens, closes and types into the console. Returns whether
+    /// it's open, in which case the run stays frozen
+    fn update_console(&mut self, ctx: &mut Context) -> bool {
+        let Some(console) = &mut self.console else {
+            return false;
+        };
+
+        if input::is_key_pressed(ctx, CONSOLE_KEY)
+            || (console.is_open()
+                && input::is_key_pressed(ctx, Key::Escape))
+        {
+            console.toggle(ctx);
+            return true;
+        }
+
+        if !console.is_open() {
+            return false;
+        }
+
+        if let Some(line) = console.update(ctx) {
+            self.run_command(&line);
+        }
+
+        true
+    }
+
+    /// The angle (in degrees) from the player to the cursor,
+    /// while aiming with the mouse and holding its left button.
+    /// The cursor is brought onto the canvas first, so that it
+    /// lines up in every scaling mode
+    fn mouse_fire_angle(
+        &self,
+        ctx: &Context,
+        shared: &Shared,
+    ) -> Option<f32> {
+        if !self.mouse_aim
+            || !input::is_mouse_button_down(
+                ctx,
+                MouseButton::Left,
+            )
+        {
+            return None;
+        }
+
+        let cursor = shared.scaler.mouse_position(ctx);
+        let player = self.simulation.player_manager().player();
+
+        Some(player.angle_to_pos(cursor).to_degrees())
+    }
+
+    fn run_command(&mut self, line: &str) {
+        let Some(console) = &mut self.console else {
+            return;
+        };
+
+        let command = match Command::parse(line) {
+            Ok(Command::Clear) => {
+                console.clear();
+                return;
+            }
+            Ok(command) => command,
+            Err(err) => {
+                console.print(&format!("error: {err}"));
+                return;
+            }
+        };
+
+        // A replay has to play out exactly as it was recorded
+        let changes_run = command.changes_run();
+        if changes_run {
+            if let InputSource::Replay(_) = self.input_source {
+                console.print("error: replays can't be changed");
+                return;
+            }
+        }
+
+        match command.run(&mut self.simulation) {
+            Ok(output) => console.print(&output),
+            Err(err) => {
+                console.print(&format!("error: {err}"));
+                return;
+            }
+        }
+
+        if changes_run && !self.was_changed {
+            self.was_changed = true;
+            self.input_source.finish_recording();
+            println!(
+                "[LOG] The run was changed from the console, so it won't be recorded or scored"
+            );
+        }
+    }
+}
+
+impl Scene for Playing {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        if self.update_console(ctx) {
+            return Ok(Transition::None);
+        }
+
+        if shared.config.controls.bindings.is_pressed(
+            ctx,
+            &shared.gamepads,
+            InputAction::Pause,
+        ) {
+            return Ok(self.pause(ctx, shared));
+        }
+
+        let ticks =
+            self.timestep.advance(time::get_delta_time(ctx));
+
+        let mut replay_finished = false;
+        for _ in 0..ticks {
+            // Ticks that don't advance the game are neither
+            // recorded nor played back, so that replays stay in
+            // sync with the simulation
+            if self.simulation.is_game_over() {
+                break;
+            }
+
+            // The player moves every tick, and the angle along
+            // with them
+            let mouse_angle = self.mouse_fire_angle(ctx, shared);
+            match self.input_source.next_input(
+                ctx,
+                &shared.config.controls.bindings,
+                &shared.gamepads,
+                mouse_angle,
+            ) {
+                Some(input) => self.simulation.tick(&input),
+                // Only a replay runs out of input
+                None => {
+                    replay_finished = true;
+                    break;
+                }
+            }
+
+            shared.sound.play_events(
+                ctx,
+                self.simulation.events(),
+                shared.ui_clock.now(),
+            );
+        }
+
+        if self.simulation.is_game_over() {
+            self.input_source.finish_recording();
+            println!(
+                "Game over at wave {} with a score of {} (seed {}, {})",
+                self.simulation.current_wave() + 1,
+                self.simulation.game_score(),
+                self.simulation.seed(),
+                self.simulation.difficulty().name()
+            );
+
+            // A replay's run is already in the table, if it made
+            // it there at all
+            let run = match self.input_source {
+                InputSource::Replay(_) => None,
+                InputSource::Keyboard(_) if self.was_changed => {
+                    None
+                }
+                InputSource::Keyboard(_) => {
+                    Some(HighScore::new(
+                        self.simulation.game_score(),
+                        self.simulation.current_wave() + 1,
+                        self.simulation.clock().now(),
+                        self.simulation.seed(),
+                        self.simulation.difficulty(),
+                    ))
+                }
+            };
+
+            return Ok(Transition::Push(Box::new(
+                GameOver::new(
+                    shared,
+                    self.simulation.seed(),
+                    run,
+                ),
+            )));
+        }
+
+        if replay_finished {
+            println!(
+                "Replay finished at wave {} with a score of {} (seed {}, {})",
+                self.simulation.current_wave() + 1,
+                self.simulation.game_score(),
+                self.simulation.seed(),
+                self.simulation.difficulty().name()
+            );
+
+            return Ok(Transition::Push(Box::new(
+                GameOver::replay_finished(
+                    self.simulation.seed(),
+                ),
+            )));
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        shared.renderer.draw(ctx, &self.simulation);
+        self.hud.draw(ctx, &self.simulation);
+
+        if self.mouse_aim {
+            shared.renderer.draw_crosshair(
+                ctx,
+                shared.scaler.mouse_position(ctx),
+            );
+        }
+
+        if let Some(console) = &mut self.console {
+            console.draw(ctx);
+        }
+
+        // Update the window title only once per second
+        if self
+            .window_title_update_timer
+            .is_ready(&shared.ui_clock)
+        {
+            self.window_title_update_timer
+                .reset(&shared.ui_clock);
+
+            window::set_title(
+                ctx,
+                format!(
+                    "Endless Trial - {:.0} FPS - Wave: {} - Score: {}",
+                    time::get_fps(ctx),
+                    self.simulation.current_wave() + 1,
+                    self.simulation.game_score()
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+        event: Event,
+    ) -> tetra::Result<Transition> {
+        // Pause the game while the window is in the background,
+        // until the player resumes it
+        if let Event::FocusLost = event {
+            return Ok(self.pause(ctx, shared));
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn on_resume(&mut self, _shared: &mut Shared) {
+        self.simulation.clock_mut().set_paused(false);
+    }
+
+    fn hides_cursor(&self) -> bool {
+        self.mouse_aim
+    }
+
+    fn simulation(&self) -> Option<&Simulation> {
+        Some(&self.simulation)
+    }
+}
@@ -1,22 +1,23 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use arrayvec::ArrayVec;
 use rand::{
     distributions::Standard, prelude::Distribution, Rng,
 };
-use tetra::{
-    graphics::{DrawParams, Rectangle},
-    math::Vec2,
-    Context,
-};
+use tetra::math::Vec2;
 
 use crate::{
-    humanoid::Humanoid, panel::Panel, textures::PowerUpTextures,
+    clock::GameClock,
+    event::GameEvent,
+    hitbox::{Collider, Hitbox},
+    humanoid::{Humanoid, FLICKER_INTERVAL},
+    spatial::SpatialGrid,
     timer::Timer,
 };
 
-/// New power-ups spawn every 5 seconds
-const POWER_UP_SPAWN_INTERVAL: Duration = Duration::from_secs(3);
+/// How long a picked-up power-up stays active
+const POWER_UP_ACTIVE_INTERVAL: Duration =
+    Duration::from_secs(5);
 
 /// Power-ups, after spawned, are available to be picked up
 /// within 10 seconds from spawning
@@ -32,6 +33,39 @@ pub enum PowerUpKind {
     TripleShooting,
 }
 
+/// Hearts are drawn at their size of 32x32 and every other
+/// power-up at 40x40, both from their top-left corner
+const HEART_HITBOX: Hitbox =
+    Hitbox::aabb(32.0, 32.0, Vec2 { x: 16.0, y: 16.0 });
+const POWER_UP_HITBOX: Hitbox =
+    Hitbox::aabb(40.0, 40.0, Vec2 { x: 20.0, y: 20.0 });
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::AdditionalHeart,
+        PowerUpKind::FasterShooting,
+        PowerUpKind::FasterRunning,
+        PowerUpKind::TripleShooting,
+    ];
+
+    /// The name of the power-up in console commands
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::AdditionalHeart => "heart",
+            PowerUpKind::FasterShooting => "faster_shooting",
+            PowerUpKind::FasterRunning => "faster_running",
+            PowerUpKind::TripleShooting => "triple_shooting",
+        }
+    }
+
+    pub const fn hitbox(self) -> Hitbox {
+        match self {
+            PowerUpKind::AdditionalHeart => HEART_HITBOX,
+            _ => POWER_UP_HITBOX,
+        }
+    }
+}
+
 impl From<PowerUpKind> for u8 {
     fn from(kind: PowerUpKind) -> Self {
         match kind {
@@ -44,7 +78,8 @@ impl From<PowerUpKind> for u8 {
 }
 
 pub struct ActivePowerUps {
-    pub slots: [Option<Instant>; 3],
+    /// The moment (in game time) each power-up was activated
+    pub slots: [Option<Duration>; 3],
 }
 
 impl ActivePowerUps {
@@ -90,13 +125,31 @@ impl ActivePowerUps {
         power_ups.into_iter()
     }
 
-    pub fn activate_power_up(&mut self, kind: PowerUpKind) {
+    pub fn activate_power_up(
+        &mut self,
+        kind: PowerUpKind,
+        clock: &GameClock,
+    ) {
         let idx: u8 = kind.into();
 
-        self.slots[idx as usize - 1] = Some(Instant::now());
+        self.slots[idx as usize - 1] = Some(clock.now());
     }
 
-    fn get(&self, kind: PowerUpKind) -> Option<Instant> {
+    /// Drops the power-ups that have been active for long
+    /// enough
+    pub fn clean_up(&mut self, clock: &GameClock) {
+        for power_up in self.slots.iter_mut() {
+            if let Some(moment_started) = power_up {
+                if clock.now().saturating_sub(*moment_started)
+                    >= POWER_UP_ACTIVE_INTERVAL
+                {
+                    *power_up = None;
+                }
+            }
+        }
+    }
+
+    fn get(&self, kind: PowerUpKind) -> Option<Duration> {
         let idx: u8 = kind.into();
 
         self.slots[idx as usize - 1]
@@ -104,7 +157,7 @@ impl ActivePowerUps {
 }
 
 #[derive(Debug)]
-struct PowerUp {
+pub struct PowerUp {
     /// What sort of power-up this iss
     kind: PowerUpKind,
     /// Times how long this power-up will be available for
@@ -114,27 +167,74 @@ struct PowerUp {
     /// Whether or not this power-up has been consumed
     was_consumed: bool,
     /// Set if the power-up is flickering (that is, next to
-    /// expirating)
-    flickering: u8,
+    /// expirating). Holds how many seconds of flickering are
+    /// left
+    flickering: f32,
 }
 
 impl PowerUp {
-    pub fn is_expired(&self) -> bool {
-        self.expiration_timer.is_ready()
+    pub fn kind(&self) -> PowerUpKind {
+        self.kind
+    }
+
+    pub fn position(&self) -> Vec2<f32> {
+        self.position
+    }
+
+    /// Whether or not this power-up should be drawn in the
+    /// current frame
+    pub fn is_visible(&self) -> bool {
+        let phase = (self.flickering / FLICKER_INTERVAL) as u32;
+
+        self.flickering == 0.0 || !phase.is_multiple_of(2)
     }
 
-    pub fn flicker_if_almost_expiring(&mut self) {
+    /// The area a humanoid has to touch to pick this power-up
+    /// up
+    pub fn collider(&self) -> Collider {
+        self.kind.hitbox().at(self.position)
+    }
+
+    /// Hands this power-up over to the humanoid that picked it
+    /// up
+    fn consume(
+        &mut self,
+        humanoid: &mut Humanoid,
+        clock: &GameClock,
+    ) {
+        self.was_consumed = true;
+        match self.kind {
+            PowerUpKind::AdditionalHeart => {
+                humanoid.hearts =
+                    humanoid.hearts.saturating_add(1)
+            }
+            power_up => {
+                humanoid
+                    .power_ups
+                    .activate_power_up(power_up, clock);
+            }
+        }
+    }
+
+    pub fn is_expired(&self, clock: &GameClock) -> bool {
+        self.expiration_timer.is_ready(clock)
+    }
+
+    pub fn flicker_if_almost_expiring(
+        &mut self,
+        clock: &GameClock,
+    ) {
         // If flickering is already set then do nothing
-        if self.flickering > 0 {
+        if self.flickering > 0.0 {
             return;
         }
 
-        let elapsed = self.expiration_timer.elapsed();
+        let elapsed = self.expiration_timer.elapsed(clock);
         if elapsed > Duration::from_secs(8) {
             // Only two more seconds available to get the
             // power-up, so we'll signal this to the
             // player through flickering
-            self.flickering = 120;
+            self.flickering = 2.0;
         }
     }
 }
@@ -157,192 +257,106 @@ pub struct PowerUpManager {
     // The power-ups laying on the ground
     powerups: Vec<PowerUp>,
     spawn_timer: Timer,
-    panel: Panel,
-    power_up_textures: PowerUpTextures,
 }
 
 impl PowerUpManager {
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(
+        clock: &GameClock,
+        spawn_interval: Duration,
+    ) -> Self {
         Self {
-            power_up_textures: PowerUpTextures::load(ctx),
             powerups: Vec::with_capacity(5),
             spawn_timer: Timer::start_now_with_interval(
-                POWER_UP_SPAWN_INTERVAL,
+                clock,
+                spawn_interval,
             ),
-            panel: Panel::new(ctx),
         }
     }
 
+    /// Changes how long to wait between spawning two power-ups
+    pub fn set_spawn_interval(&mut self, interval: Duration) {
+        self.spawn_timer.set_interval(interval);
+    }
+
+    /// The power-ups currently laying on the ground
+    pub fn power_ups(&self) -> &[PowerUp] {
+        &self.powerups
+    }
+
     /// Check if the given humanoid collided with a power-up
     /// laying in the ground.
     pub fn check_for_collision(
         &mut self,
         humanoid: &mut Humanoid,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
     ) {
-        let pos = humanoid.position;
-        let rect = Rectangle::new(pos.x, pos.y, 16.0, 16.0);
+        let humanoid_collider = humanoid.collider();
         for powerup in &mut self.powerups {
-            let powerup_rect = Rectangle::new(
-                powerup.position.x,
-                powerup.position.y,
-                32.0,
-                32.0,
-            );
-
-            if powerup_rect.intersects(&rect) {
-                powerup.was_consumed = true;
-                match powerup.kind {
-                    PowerUpKind::AdditionalHeart => {
-                        humanoid.hearts += 1
-                    }
-                    power_up => {
-                        humanoid
-                            .power_ups
-                            .activate_power_up(power_up);
-                    }
-                }
+            if powerup.collider().intersects(&humanoid_collider)
+            {
+                powerup.consume(humanoid, clock);
+                events.push(GameEvent::PowerUpPickedUp);
             }
         }
     }
 
-    pub fn can_spawn(&self) -> bool {
-        self.spawn_timer.is_ready()
-    }
-
-    fn draw_powerup_bar(
-        &self,
-        ctx: &mut Context,
-        player_power_ups: &ActivePowerUps,
+    /// Check if any enemy collided with a power-up laying in
+    /// the ground, `enemy_grid` holding the enemies' rectangles
+    pub fn check_for_enemy_collisions(
+        &mut self,
+        enemy_grid: &SpatialGrid,
+        enemies: &mut [Humanoid],
+        clock: &GameClock,
     ) {
-        let active_powerups_no = player_power_ups.len();
-        if active_powerups_no == 0 {
-            return;
-        }
-
-        let width = (active_powerups_no as f32) * 16.0 + 10.5;
-
-        self.panel.sprite.draw_nine_slice(
-            ctx,
-            &self.panel.config,
-            width,
-            26.0,
-            DrawParams::new()
-                .position(Vec2::new(768.0 - width, 60.0)),
-        );
-
-        for (kind, spacing) in
-            player_power_ups.iter().zip(0..active_powerups_no)
-        {
-            let spacing = spacing as f32;
-            match kind {
-                PowerUpKind::AdditionalHeart => unreachable!(),
-                PowerUpKind::FasterShooting => self
-                    .power_up_textures
-                    .fire_scroll_sprite
-                    .draw(
-                        ctx,
-                        DrawParams::new().position(Vec2 {
-                            x: 746. - 16.0 * spacing,
-                            y: 60. + 4.,
-                        }),
-                    ),
-                PowerUpKind::FasterRunning => {
-                    self.power_up_textures.boot_sprite.draw(
-                        ctx,
-                        DrawParams::new().position(Vec2 {
-                            x: 746. - 16.0 * spacing,
-                            y: 60. + 4.,
-                        }),
-                    )
-                }
-                PowerUpKind::TripleShooting => {
-                    self.power_up_textures.ring_sprite.draw(
-                        ctx,
-                        DrawParams::new().position(Vec2 {
-                            x: 746. - 16.0 * spacing,
-                            y: 60. + 4.,
-                        }),
-                    )
-                }
-            }
+        for powerup in &mut self.powerups {
+            enemy_grid.for_each_intersecting(
+                &powerup.collider(),
+                |enemy| {
+                    powerup.consume(&mut enemies[enemy], clock)
+                },
+            );
         }
     }
 
-    pub fn draw(
-        &mut self,
-        ctx: &mut Context,
-        player_power_ups: &ActivePowerUps,
-    ) {
-        self.draw_powerup_bar(ctx, player_power_ups);
-
-        for powerup in self.powerups.iter_mut() {
-            if powerup.flickering > 0 {
-                powerup.flickering -= 1;
-                if powerup.flickering % 2 == 0 {
-                    continue;
-                }
-            }
-
-            match powerup.kind {
-                PowerUpKind::AdditionalHeart => {
-                    self.power_up_textures.heart_sprite.draw(
-                        ctx,
-                        DrawParams::new()
-                            .position(powerup.position),
-                    )
-                }
-                PowerUpKind::FasterShooting => self
-                    .power_up_textures
-                    .fire_scroll_sprite
-                    .draw(
-                        ctx,
-                        DrawParams::new()
-                            .position(powerup.position)
-                            .scale(Vec2::new(2.5, 2.5)),
-                    ),
-                PowerUpKind::FasterRunning => {
-                    self.power_up_textures.boot_sprite.draw(
-                        ctx,
-                        DrawParams::new()
-                            .position(powerup.position)
-                            .scale(Vec2::new(2.5, 2.5)),
-                    )
-                }
-                PowerUpKind::TripleShooting => {
-                    self.power_up_textures.ring_sprite.draw(
-                        ctx,
-                        DrawParams::new()
-                            .position(powerup.position)
-                            .scale(Vec2::new(2.5, 2.5)),
-                    )
-                }
-            }
-        }
+    pub fn can_spawn(&self, clock: &GameClock) -> bool {
+        self.spawn_timer.is_ready(clock)
     }
 
-    pub fn update(&mut self) {
+    /// Drops consumed or expired power-ups and advances the
+    /// flickering of the remaining ones
+    pub fn update(&mut self, clock: &GameClock) {
         self.powerups
-            .retain(|p| !p.was_consumed && !p.is_expired());
+            .retain(|p| !p.was_consumed && !p.is_expired(clock));
 
-        self.powerups
-            .iter_mut()
-            .for_each(PowerUp::flicker_if_almost_expiring);
+        for powerup in self.powerups.iter_mut() {
+            powerup.flickering = (powerup.flickering
+                - clock.delta_secs())
+            .max(0.0);
+            powerup.flicker_if_almost_expiring(clock);
+        }
     }
 
     pub fn advance<R: Rng>(
         &mut self,
         rng: &mut R,
         player: &mut Humanoid,
+        clock: &GameClock,
+        events: &mut Vec<GameEvent>,
     ) {
-        if self.can_spawn() {
-            self.spawn_power_up(rng);
+        if self.can_spawn(clock) {
+            self.spawn_power_up(rng, clock);
         }
 
-        self.check_for_collision(player);
+        self.check_for_collision(player, clock, events);
     }
 
-    pub fn spawn_power_up<R: Rng>(&mut self, rng: &mut R) {
-        self.spawn_timer.reset();
+    pub fn spawn_power_up<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        clock: &GameClock,
+    ) {
+        self.spawn_timer.reset(clock);
 
         let position = Vec2 {
             x: rng.gen_range(0.0..200.0) * 4.0,
@@ -353,12 +367,43 @@ impl PowerUpManager {
             kind: rng.gen(),
             position,
             expiration_timer: Timer::start_now_with_interval(
+                clock,
                 POWER_UP_AVAILABILITY_INTERVAL,
             ),
             was_consumed: false,
-            flickering: 0,
+            flickering: 0.0,
         };
 
         self.powerups.push(power_up);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{difficulty::Difficulty, player::PlayerManager};
+
+    #[test]
+    fn hearts_stop_at_the_maximum() {
+        let clock = GameClock::new();
+        let mut player_mgr =
+            PlayerManager::new(&clock, Difficulty::Normal);
+        let player = player_mgr.player_mut();
+        player.hearts = u8::MAX;
+
+        let mut heart = PowerUp {
+            kind: PowerUpKind::AdditionalHeart,
+            expiration_timer: Timer::start_now_with_interval(
+                &clock,
+                Duration::from_secs(10),
+            ),
+            position: player.position,
+            was_consumed: false,
+            flickering: 0.0,
+        };
+        heart.consume(player, &clock);
+
+        assert_eq!(player.hearts, u8::MAX);
+        assert!(heart.was_consumed);
+    }
+}
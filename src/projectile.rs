@@ -1,16 +1,17 @@
 use core::f32;
 
-use tetra::{
-    graphics::{animation::Animation, DrawParams},
-    math::Vec2,
-    Context,
-};
+use tetra::math::Vec2;
 
-use crate::{traits::Cleanable, BOUNDS};
+use crate::{
+    hitbox::{Collider, Hitbox},
+    traits::Cleanable,
+    BOUNDS,
+};
 
 #[derive(Clone)]
 pub struct Projectile {
     position: Vec2<f32>,
+    /// How many pixels per second this projectile travels
     velocity: Vec2<f32>,
     angle_rad: f32,
 }
@@ -19,11 +20,35 @@ impl Projectile {
     pub fn position(&self) -> Vec2<f32> {
         self.position
     }
+
+    pub fn angle_rad(&self) -> f32 {
+        self.angle_rad
+    }
+
+    /// How many pixels this projectile moves every second, and
+    /// in which direction
+    pub fn velocity(&self) -> Vec2<f32> {
+        Vec2::new(
+            f32::cos(self.angle_rad),
+            -f32::sin(self.angle_rad),
+        ) * self.velocity
+    }
 }
 
+/// Fireballs are drawn from 32x32 frames centered on their
+/// position, the ball itself being about 19 pixels wide
+pub const FIREBALL_HITBOX: Hitbox =
+    Hitbox::circle(9.0, Vec2 { x: 0.0, y: 0.0 });
+
+/// Cannonballs are drawn the same way, and are about 28 pixels
+/// wide counting their glow
+pub const CANNONBALL_HITBOX: Hitbox =
+    Hitbox::circle(12.0, Vec2 { x: 0.0, y: 0.0 });
+
 pub struct ProjectileManager {
     projectiles: Vec<Projectile>,
-    animation: Animation,
+    /// The hitbox shared by every projectile of this manager
+    hitbox: Hitbox,
 }
 
 impl Cleanable for ProjectileManager {
@@ -38,10 +63,10 @@ impl Cleanable for ProjectileManager {
 }
 
 impl ProjectileManager {
-    pub fn new(animation: Animation) -> Self {
+    pub fn new(hitbox: Hitbox) -> Self {
         Self {
             projectiles: Vec::with_capacity(48),
-            animation,
+            hitbox,
         }
     }
 
@@ -93,16 +118,12 @@ impl ProjectileManager {
         self.projectiles.push(fireball);
     }
 
-    pub fn advance_animation(&mut self, ctx: &mut Context) {
-        self.animation.advance(ctx);
-
+    /// Moves every projectile `dt` seconds along its trajectory
+    pub fn advance(&mut self, dt: f32) {
         self.clean_up();
 
         for fireball in &mut self.projectiles {
-            let ang_rad = fireball.angle_rad;
-            fireball.position +=
-                Vec2::new(f32::cos(ang_rad), -f32::sin(ang_rad))
-                    * fireball.velocity;
+            fireball.position += fireball.velocity() * dt;
         }
     }
 
@@ -110,15 +131,14 @@ impl ProjectileManager {
         &self.projectiles
     }
 
-    pub fn draw(&self, ctx: &mut Context) {
-        for fireball in &self.projectiles {
-            self.animation.draw(
-                ctx,
-                DrawParams::new()
-                    .position(fireball.position)
-                    .origin(Vec2::new(16.0, 16.0))
-                    .rotation(fireball.angle_rad),
-            )
-        }
+    /// Where each projectile can hit, in the same order as
+    /// [`ProjectileManager::projectiles`]
+    pub fn colliders(
+        &self,
+    ) -> impl Iterator<Item = Collider> + '_ {
+        let hitbox = self.hitbox;
+        self.projectiles.iter().map(move |projectile| {
+            hitbox.at(projectile.position)
+        })
     }
 }
@@ -0,0 +1,346 @@
+use std::time::Duration;
+
+use tetra::{
+    graphics::{animation::Animation, DrawParams, Texture},
+    math::Vec2,
+    Context,
+};
+
+use crate::{
+    animation::{
+        CannonballAnimation, ExplosionAnimation,
+        FireballAnimation, HumanoidAnimation, SmokeAnimation,
+    },
+    background::Background,
+    healthbar::HealthBar,
+    humanoid::Humanoid,
+    oneoffanim::OneOffAnimation,
+    panel::{GameOverPanel, Panel},
+    powerup::{ActivePowerUps, PowerUpKind},
+    projectile::Projectile,
+    resources,
+    simulation::Simulation,
+    textures::{GruntTextures, PowerUpTextures},
+    Direction,
+};
+
+/// Draws a [`Simulation`] using the textures it loaded into
+/// GPU memory
+pub struct Renderer {
+    /// The textures of the game's background
+    background: Background,
+    health_bar: HealthBar,
+    game_over_panel: GameOverPanel,
+    /// The panel behind the bar of active power-ups
+    panel: Panel,
+    power_up_textures: PowerUpTextures,
+    player_animation: HumanoidAnimation,
+    /// All enemy textures already loaded into memory
+    grunt_textures: GruntTextures,
+    fireball_animation: Animation,
+    cannonball_animation: Animation,
+    explosion_animation: Animation,
+    smoke_animation: Animation,
+    /// Drawn at the cursor when aiming with the mouse
+    crosshair: Texture,
+    /// The game time of the last frame we drew, so that our
+    /// animations stop whenever the game clock does
+    last_drawn: Duration,
+}
+
+impl Renderer {
+    pub fn new(ctx: &mut Context) -> Self {
+        let player_texture =
+            Texture::from_encoded(ctx, resources::HERO).unwrap();
+
+        Self {
+            background: Background::new(ctx),
+            health_bar: HealthBar::new(ctx),
+            game_over_panel: GameOverPanel::new(ctx),
+            panel: Panel::new(ctx),
+            power_up_textures: PowerUpTextures::load(ctx),
+            player_animation: HumanoidAnimation::new(
+                player_texture,
+            ),
+            grunt_textures: GruntTextures::load(ctx),
+            fireball_animation: FireballAnimation::build(ctx),
+            cannonball_animation: CannonballAnimation::build(
+                ctx,
+            ),
+            explosion_animation: ExplosionAnimation::build(ctx),
+            smoke_animation: SmokeAnimation::build(ctx),
+            crosshair: Texture::from_encoded(
+                ctx,
+                resources::CROSSHAIR,
+            )
+            .unwrap(),
+            last_drawn: Duration::ZERO,
+        }
+    }
+
+    pub fn draw_background(&self, ctx: &mut Context) {
+        self.background.draw(ctx);
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, sim: &Simulation) {
+        self.background.draw(ctx);
+
+        let player_manager = sim.player_manager();
+        let enemy_mgr = sim.enemy_mgr();
+
+        let now = sim.clock().now();
+        let elapsed = now.saturating_sub(self.last_drawn);
+        self.last_drawn = now;
+
+        self.fireball_animation.advance_by(elapsed);
+        self.cannonball_animation.advance_by(elapsed);
+
+        Self::draw_humanoid(
+            ctx,
+            &mut self.player_animation,
+            player_manager.player(),
+        );
+        Self::draw_projectiles(
+            ctx,
+            &self.fireball_animation,
+            player_manager.fireballs(),
+        );
+
+        for enemy in &enemy_mgr.enemies {
+            let animation = self
+                .grunt_textures
+                .animation_mut(enemy.kind(), enemy.sprite);
+            Self::draw_humanoid(ctx, animation, enemy);
+        }
+        Self::draw_projectiles(
+            ctx,
+            &self.cannonball_animation,
+            enemy_mgr.cannonballs(),
+        );
+
+        self.draw_power_ups(ctx, sim);
+        self.health_bar.draw(ctx, player_manager.hearts());
+
+        let one_off_anim_mgr = sim.one_off_anim_mgr();
+        Self::draw_one_off_animations(
+            ctx,
+            &mut self.explosion_animation,
+            one_off_anim_mgr.explosions(),
+        );
+        Self::draw_one_off_animations(
+            ctx,
+            &mut self.smoke_animation,
+            one_off_anim_mgr.smokes(),
+        );
+    }
+
+    /// Draws the crosshair centered on `position`
+    pub fn draw_crosshair(
+        &self,
+        ctx: &mut Context,
+        position: Vec2<f32>,
+    ) {
+        let (width, height) = self.crosshair.size();
+
+        self.crosshair.draw(
+            ctx,
+            DrawParams::new()
+                .position(position)
+                .origin(Vec2::new(
+                    width as f32 / 2.0,
+                    height as f32 / 2.0,
+                ))
+                .scale(Vec2::new(2.0, 2.0)),
+        );
+    }
+
+    /// Draws the panel shown once the player has died, with a
+    /// hint on what to do next
+    pub fn draw_game_over_panel(
+        &mut self,
+        ctx: &mut Context,
+        title: &'static str,
+        seed: u64,
+        hint: &str,
+    ) {
+        self.game_over_panel.draw(ctx, title, seed, hint);
+    }
+
+    fn draw_humanoid(
+        ctx: &mut Context,
+        animation: &mut HumanoidAnimation,
+        humanoid: &Humanoid,
+    ) {
+        if !humanoid.is_visible() {
+            return;
+        }
+
+        let scale = Vec2::new(3., 3.);
+        let (animation, scale) = match humanoid.direction {
+            Direction::North => (&mut animation.backside, scale),
+            Direction::West => (&mut animation.leftside, scale),
+            Direction::East => {
+                (&mut animation.leftside, Vec2 { x: -3., y: 3. })
+            }
+            Direction::South => {
+                (&mut animation.frontside, scale)
+            }
+        };
+
+        animation.set_current_frame_index(
+            humanoid.walking_frame as usize,
+        );
+        animation.draw(
+            ctx,
+            DrawParams::new()
+                .position(humanoid.position)
+                .origin(Vec2::new(8.0, 8.0))
+                .scale(scale),
+        );
+    }
+
+    fn draw_projectiles(
+        ctx: &mut Context,
+        animation: &Animation,
+        projectiles: &[Projectile],
+    ) {
+        for projectile in projectiles {
+            animation.draw(
+                ctx,
+                DrawParams::new()
+                    .position(projectile.position())
+                    .origin(Vec2::new(16.0, 16.0))
+                    .rotation(projectile.angle_rad()),
+            )
+        }
+    }
+
+    fn draw_one_off_animations(
+        ctx: &mut Context,
+        animation: &mut Animation,
+        one_off_animations: &[OneOffAnimation],
+    ) {
+        for one_off_animation in one_off_animations {
+            animation.set_current_frame_index(
+                one_off_animation.current_frame() as usize,
+            );
+            animation.draw(
+                ctx,
+                DrawParams::new()
+                    .position(one_off_animation.position())
+                    .origin(Vec2::new(16.0, 16.0)),
+            );
+        }
+    }
+
+    fn draw_powerup_bar(
+        &self,
+        ctx: &mut Context,
+        player_power_ups: &ActivePowerUps,
+    ) {
+        let active_powerups_no = player_power_ups.len();
+        if active_powerups_no == 0 {
+            return;
+        }
+
+        let width = (active_powerups_no as f32) * 16.0 + 10.5;
+
+        self.panel.sprite.draw_nine_slice(
+            ctx,
+            &self.panel.config,
+            width,
+            26.0,
+            DrawParams::new()
+                .position(Vec2::new(768.0 - width, 60.0)),
+        );
+
+        for (kind, spacing) in
+            player_power_ups.iter().zip(0..active_powerups_no)
+        {
+            let spacing = spacing as f32;
+            match kind {
+                PowerUpKind::AdditionalHeart => unreachable!(),
+                PowerUpKind::FasterShooting => self
+                    .power_up_textures
+                    .fire_scroll_sprite
+                    .draw(
+                        ctx,
+                        DrawParams::new().position(Vec2 {
+                            x: 746. - 16.0 * spacing,
+                            y: 60. + 4.,
+                        }),
+                    ),
+                PowerUpKind::FasterRunning => {
+                    self.power_up_textures.boot_sprite.draw(
+                        ctx,
+                        DrawParams::new().position(Vec2 {
+                            x: 746. - 16.0 * spacing,
+                            y: 60. + 4.,
+                        }),
+                    )
+                }
+                PowerUpKind::TripleShooting => {
+                    self.power_up_textures.ring_sprite.draw(
+                        ctx,
+                        DrawParams::new().position(Vec2 {
+                            x: 746. - 16.0 * spacing,
+                            y: 60. + 4.,
+                        }),
+                    )
+                }
+            }
+        }
+    }
+
+    fn draw_power_ups(
+        &self,
+        ctx: &mut Context,
+        sim: &Simulation,
+    ) {
+        self.draw_powerup_bar(
+            ctx,
+            &sim.player_manager().player().power_ups,
+        );
+
+        for powerup in sim.power_up_mgr().power_ups() {
+            if !powerup.is_visible() {
+                continue;
+            }
+
+            match powerup.kind() {
+                PowerUpKind::AdditionalHeart => {
+                    self.power_up_textures.heart_sprite.draw(
+                        ctx,
+                        DrawParams::new()
+                            .position(powerup.position()),
+                    )
+                }
+                PowerUpKind::FasterShooting => self
+                    .power_up_textures
+                    .fire_scroll_sprite
+                    .draw(
+                        ctx,
+                        DrawParams::new()
+                            .position(powerup.position())
+                            .scale(Vec2::new(2.5, 2.5)),
+                    ),
+                PowerUpKind::FasterRunning => {
+                    self.power_up_textures.boot_sprite.draw(
+                        ctx,
+                        DrawParams::new()
+                            .position(powerup.position())
+                            .scale(Vec2::new(2.5, 2.5)),
+                    )
+                }
+                PowerUpKind::TripleShooting => {
+                    self.power_up_textures.ring_sprite.draw(
+                        ctx,
+                        DrawParams::new()
+                            .position(powerup.position())
+                            .scale(Vec2::new(2.5, 2.5)),
+                    )
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,517 @@
+//! Recording and playback of the inputs of a run.
+//!
+//! Since a [`Simulation`](crate::simulation::Simulation) is
+//! fully determined by its seed and the input of every tick,
+//! storing those is enough to play a run back exactly.
+//!
+//! A replay file is laid out as follows, with every integer in
+//! little-endian:
+//!
+//! | Field      | Size    | Contents                         |
+//! |------------|---------|----------------------------------|
+//! | Magic      | 4 bytes | `ETRP`                           |
+//! | Version    | 1 byte  | Currently 2                      |
+//! | Seed       | 8 bytes | The seed of the simulation       |
+//! | Difficulty | 1 byte  | From 0 (easy) to 3 (nightmare)   |
+//! | Runs       | 6 bytes | Repeated until the end of file   |
+//!
+//! Version 1 replays have no difficulty byte, and were all
+//! played on normal.
+//!
+//! Consecutive ticks with the same input are stored as a
+//! single run:
+//!
+//! | Field      | Size   | Contents                            |
+//! |------------|--------|-------------------------------------|
+//! | Movement X | 1 byte | `i8`, -127 to 127 maps to -1 to 1   |
+//! | Movement Y | 1 byte | `i8`, -127 to 127 maps to -1 to 1   |
+//! | Fire angle | 2 byte | `u16`, hundredths of a degree, or   |
+//! |            |        | `0xFFFF` if not firing              |
+//! | Length     | 2 byte | `u16`, how many ticks the run lasts |
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+
+use tetra::math::Vec2;
+
+use crate::{difficulty::Difficulty, input::InputFrame};
+
+const MAGIC: &[u8; 4] = b"ETRP";
+const VERSION: u8 = 2;
+
+/// Size of the magic, version and seed
+const V1_HEADER_LEN: usize = 4 + 1 + 8;
+/// Size of the magic, version, seed and difficulty
+const HEADER_LEN: usize = V1_HEADER_LEN + 1;
+/// Size of an encoded input plus the length of its run
+const RUN_LEN: usize = 6;
+
+/// Stands for "not firing" in an encoded fire angle
+const NOT_FIRING: u16 = u16::MAX;
+
+/// An [`InputFrame`] as stored in a replay file
+type EncodedInput = [u8; 4];
+
+fn encode_input(input: &InputFrame) -> EncodedInput {
+    let encode_axis = |axis: f32| {
+        (axis.clamp(-1.0, 1.0) * 127.0).round() as i8
+    };
+
+    let fire_angle = match input.fire_angle {
+        Some(angle) => {
+            (angle.rem_euclid(360.0) * 100.0).round() as u16
+                % 36000
+        }
+        None => NOT_FIRING,
+    };
+
+    let [angle_lo, angle_hi] = fire_angle.to_le_bytes();
+
+    [
+        encode_axis(input.movement.x) as u8,
+        encode_axis(input.movement.y) as u8,
+        angle_lo,
+        angle_hi,
+    ]
+}
+
+fn decode_input(encoded: EncodedInput) -> InputFrame {
+    let decode_axis = |axis: u8| (axis as i8) as f32 / 127.0;
+
+    let fire_angle =
+        match u16::from_le_bytes([encoded[2], encoded[3]]) {
+            NOT_FIRING => None,
+            angle => Some(angle as f32 / 100.0),
+        };
+
+    InputFrame {
+        movement: Vec2 {
+            x: decode_axis(encoded[0]),
+            y: decode_axis(encoded[1]),
+        },
+        fire_angle,
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes the input of every tick into a replay file as the
+/// game is played
+pub struct ReplayRecorder {
+    file: BufWriter<File>,
+    difficulty: Difficulty,
+    /// The input of the run that's still going on, and how
+    /// many ticks it has lasted so far
+    pending: Option<(EncodedInput, u16)>,
+}
+
+impl ReplayRecorder {
+    /// Creates the replay file of a run with the given seed
+    /// and difficulty
+    pub fn create(
+        path: &Path,
+        seed: u64,
+        difficulty: Difficulty,
+    ) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        file.write_all(&seed.to_le_bytes())?;
+        file.write_all(&[difficulty as u8])?;
+
+        Ok(Self {
+            file,
+            difficulty,
+            pending: None,
+        })
+    }
+
+    /// The difficulty of the run being recorded
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Records the input of a tick.
+    ///
+    /// Returns the input as it will be played back, which is
+    /// what should be fed to the simulation so that the
+    /// replay matches the run exactly.
+    pub fn record(
+        &mut self,
+        input: &InputFrame,
+    ) -> io::Result<InputFrame> {
+        let encoded = encode_input(input);
+
+        match &mut self.pending {
+            Some((pending, length))
+                if *pending == encoded && *length < u16::MAX =>
+            {
+                *length += 1;
+            }
+            _ => {
+                self.write_pending()?;
+                self.pending = Some((encoded, 1));
+            }
+        }
+
+        Ok(decode_input(encoded))
+    }
+
+    /// Writes the last run and flushes the file
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_pending()?;
+        self.file.flush()
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        if let Some((encoded, length)) = self.pending.take() {
+            self.file.write_all(&encoded)?;
+            self.file.write_all(&length.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ReplayRecorder {
+    /// Makes sure a run cut short (e.g. by closing the window)
+    /// still leaves a playable replay behind
+    fn drop(&mut self) {
+        let _ = self.write_pending();
+        let _ = self.file.flush();
+    }
+}
+
+/// Hands out the inputs stored in a replay file, one tick at a
+/// time
+pub struct ReplayPlayer {
+    seed: u64,
+    difficulty: Difficulty,
+    /// The runs of the replay, in reverse order so that the
+    /// next one can be popped
+    runs: Vec<(InputFrame, u16)>,
+}
+
+impl ReplayPlayer {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.len() < V1_HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err(invalid_data("not a replay file"));
+        }
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[5..V1_HEADER_LEN]);
+
+        let (difficulty, body) = match bytes[4] {
+            1 => (Difficulty::Normal, &bytes[V1_HEADER_LEN..]),
+            VERSION if bytes.len() >= HEADER_LEN => {
+                let difficulty = Difficulty::ALL
+                    .get(bytes[V1_HEADER_LEN] as usize)
+                    .copied()
+                    .ok_or_else(|| {
+                        invalid_data("unknown difficulty")
+                    })?;
+
+                (difficulty, &bytes[HEADER_LEN..])
+            }
+            VERSION => {
+                return Err(invalid_data("not a replay file"))
+            }
+            version => {
+                return Err(invalid_data(&format!(
+                    "unsupported replay version {version}"
+                )))
+            }
+        };
+
+        if !body.len().is_multiple_of(RUN_LEN) {
+            return Err(invalid_data("truncated replay file"));
+        }
+
+        let runs = body
+            .chunks_exact(RUN_LEN)
+            .rev()
+            .map(|run| {
+                let input = decode_input([
+                    run[0], run[1], run[2], run[3],
+                ]);
+                let length =
+                    u16::from_le_bytes([run[4], run[5]]);
+
+                (input, length)
+            })
+            .filter(|(_, length)| *length > 0)
+            .collect();
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            difficulty,
+            runs,
+        })
+    }
+
+    /// The seed the recorded run was played with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The difficulty the recorded run was played on
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// The input of the next tick, or `None` once the replay
+    /// is over
+    pub fn next_input(&mut self) -> Option<InputFrame> {
+        let (input, length) = self.runs.last_mut()?;
+        let input = *input;
+
+        *length -= 1;
+        if *length == 0 {
+            self.runs.pop();
+        }
+
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::TempDir;
+
+    fn frame(
+        x: f32,
+        y: f32,
+        fire_angle: Option<f32>,
+    ) -> InputFrame {
+        InputFrame {
+            movement: Vec2::new(x, y),
+            fire_angle,
+        }
+    }
+
+    /// A header for the given version, seed 7 and, from
+    /// version 2 on, hard difficulty
+    fn header(version: u8) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(version);
+        bytes.extend_from_slice(&7u64.to_le_bytes());
+        if version >= 2 {
+            bytes.push(Difficulty::Hard as u8);
+        }
+
+        bytes
+    }
+
+    fn error_of(bytes: &[u8]) -> String {
+        match ReplayPlayer::from_bytes(bytes) {
+            Ok(_) => panic!("the replay should be rejected"),
+            Err(err) => {
+                assert_eq!(
+                    err.kind(),
+                    io::ErrorKind::InvalidData
+                );
+                err.to_string()
+            }
+        }
+    }
+
+    #[test]
+    fn inputs_survive_encoding() {
+        let inputs = [
+            frame(0.0, 0.0, None),
+            frame(1.0, -1.0, Some(0.0)),
+            frame(-1.0, 1.0, Some(359.99)),
+            frame(0.5, -0.25, Some(123.45)),
+        ];
+
+        for input in inputs {
+            let decoded = decode_input(encode_input(&input));
+
+            assert_eq!(decoded.fire_angle, input.fire_angle);
+            assert!(
+                (decoded.movement - input.movement).magnitude()
+                    < 0.01,
+                "{:?} came back as {:?}",
+                input,
+                decoded
+            );
+        }
+    }
+
+    #[test]
+    fn not_firing_is_kept_apart_from_angles() {
+        let not_firing = encode_input(&frame(0.0, 0.0, None));
+        assert_eq!(
+            u16::from_le_bytes([not_firing[2], not_firing[3]]),
+            NOT_FIRING
+        );
+
+        // No angle is ever encoded as "not firing"
+        for angle in [0.0, 359.999, 360.0, 655.35, -0.001] {
+            let input = frame(0.0, 0.0, Some(angle));
+            assert!(
+                decode_input(encode_input(&input))
+                    .fire_angle
+                    .is_some(),
+                "{} stopped firing",
+                angle
+            );
+        }
+    }
+
+    #[test]
+    fn angles_wrap_around() {
+        let decoded_angle = |angle| {
+            decode_input(encode_input(&frame(
+                0.0,
+                0.0,
+                Some(angle),
+            )))
+            .fire_angle
+            .unwrap()
+        };
+
+        assert_eq!(decoded_angle(-90.0), 270.0);
+        assert_eq!(decoded_angle(450.0), 90.0);
+        assert_eq!(decoded_angle(359.999), 0.0);
+    }
+
+    #[test]
+    fn axes_are_clamped() {
+        let decoded =
+            decode_input(encode_input(&frame(3.0, -20.0, None)));
+
+        assert_eq!(decoded.movement, Vec2::new(1.0, -1.0));
+    }
+
+    #[test]
+    fn recorded_runs_play_back_the_same() {
+        let dir = TempDir::new("replay-round-trip");
+        let path = dir.join("run.etrp");
+
+        // A long run has to be split, since a run only counts up
+        // to u16::MAX ticks
+        let mut inputs = vec![frame(1.0, 0.0, None); 70_000];
+        inputs.extend([
+            frame(0.0, 1.0, Some(90.0)),
+            frame(0.0, 1.0, Some(90.0)),
+            frame(-0.5, 0.5, Some(180.0)),
+            frame(0.0, 0.0, None),
+        ]);
+
+        let mut recorder = ReplayRecorder::create(
+            &path,
+            1234,
+            Difficulty::Easy,
+        )
+        .unwrap();
+        let recorded: Vec<_> = inputs
+            .iter()
+            .map(|input| recorder.record(input).unwrap())
+            .collect();
+        recorder.finish().unwrap();
+
+        let mut player = ReplayPlayer::open(&path).unwrap();
+
+        assert_eq!(player.seed(), 1234);
+        assert_eq!(player.difficulty(), Difficulty::Easy);
+
+        let played: Vec<_> =
+            std::iter::from_fn(|| player.next_input()).collect();
+        assert_eq!(played, recorded);
+    }
+
+    #[test]
+    fn dropping_the_recorder_keeps_the_last_run() {
+        let dir = TempDir::new("replay-dropped");
+        let path = dir.join("run.etrp");
+
+        let mut recorder =
+            ReplayRecorder::create(&path, 1, Difficulty::Normal)
+                .unwrap();
+        for _ in 0..3 {
+            recorder.record(&frame(0.0, -1.0, None)).unwrap();
+        }
+        drop(recorder);
+
+        let mut player = ReplayPlayer::open(&path).unwrap();
+
+        let played =
+            std::iter::from_fn(|| player.next_input()).count();
+        assert_eq!(played, 3);
+    }
+
+    #[test]
+    fn version_1_replays_are_played_on_normal() {
+        let mut bytes = header(1);
+        bytes.extend_from_slice(&[127, 0, 0xFF, 0xFF, 2, 0]);
+
+        let mut player =
+            ReplayPlayer::from_bytes(&bytes).unwrap();
+
+        assert_eq!(player.seed(), 7);
+        assert_eq!(player.difficulty(), Difficulty::Normal);
+        assert_eq!(
+            player.next_input(),
+            Some(frame(1.0, 0.0, None))
+        );
+        assert_eq!(
+            player.next_input(),
+            Some(frame(1.0, 0.0, None))
+        );
+        assert_eq!(player.next_input(), None);
+    }
+
+    #[test]
+    fn wrong_magic_is_rejected() {
+        let mut bytes = header(VERSION);
+        bytes[0] = b'X';
+
+        assert_eq!(error_of(&bytes), "not a replay file");
+        assert_eq!(error_of(b"ETR"), "not a replay file");
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        // A header cut off before the difficulty
+        let bytes = header(VERSION);
+        assert_eq!(
+            error_of(&bytes[..bytes.len() - 1]),
+            "not a replay file"
+        );
+
+        // A run missing its last byte
+        let mut bytes = header(VERSION);
+        bytes.extend_from_slice(&[0, 0, 0xFF, 0xFF, 1]);
+        assert_eq!(error_of(&bytes), "truncated replay file");
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert_eq!(
+            error_of(&header(VERSION + 1)),
+            format!(
+                "unsupported replay version {}",
+                VERSION + 1
+            )
+        );
+
+        let mut bytes = header(VERSION);
+        *bytes.last_mut().unwrap() = Difficulty::ALL.len() as u8;
+        assert_eq!(error_of(&bytes), "unknown difficulty");
+    }
+}
@@ -94,6 +94,10 @@ pub const HEART_32X: &[u8] =
 pub const FIRE_SCROLL: &[u8] =
     include_bytes!("../resources/sprites/scrolls/fire.png");
 
+pub const CROSSHAIR: &[u8] = include_bytes!(
+    "../resources/sprites/crosshair/crosshair.png"
+);
+
 pub const PANEL: &[u8] =
     include_bytes!("../resources/sprites/panel/panel.png");
 
@@ -111,3 +115,39 @@ pub const BOOT: &[u8] =
 
 pub const RING: &[u8] =
     include_bytes!("../resources/sprites/ring/ring.png");
+
+pub const WAVES: &str = include_str!("../resources/waves.toml");
+
+#[cfg(feature = "audio")]
+pub const FIRE_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/fire.wav");
+#[cfg(feature = "audio")]
+pub const ENEMY_FIRE_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/enemy_fire.wav");
+#[cfg(feature = "audio")]
+pub const ENEMY_HIT_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/enemy_hit.wav");
+#[cfg(feature = "audio")]
+pub const ENEMY_DEATH_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/enemy_death.wav");
+#[cfg(feature = "audio")]
+pub const PLAYER_HURT_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/player_hurt.wav");
+#[cfg(feature = "audio")]
+pub const CANNONBALL_IMPACT_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/cannonball_impact.wav");
+#[cfg(feature = "audio")]
+pub const POWER_UP_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/power_up.wav");
+#[cfg(feature = "audio")]
+pub const POWER_UP_EXPIRED_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/power_up_expired.wav");
+#[cfg(feature = "audio")]
+pub const WAVE_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/wave.wav");
+#[cfg(feature = "audio")]
+pub const GAME_OVER_SOUND: &[u8] =
+    include_bytes!("../resources/sounds/game_over.wav");
+#[cfg(feature = "audio")]
+pub const MUSIC: &[u8] =
+    include_bytes!("../resources/sounds/music.wav");
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use tetra::{
+    graphics::{scaling::ScreenScaler, text::Font},
+    Context, Event,
+};
+
+use crate::{
+    clock::GameClock, config::Config, difficulty::Difficulty,
+    gamepad::Gamepads, panel::Panel, renderer::Renderer,
+    resources, scoretable::ScoreTable, simulation::Simulation,
+    sound::SoundManager, waves::Waves,
+};
+
+/// The fonts every scene draws its text with, loaded once
+pub struct Fonts {
+    /// For titles and banners
+    pub large: Font,
+    /// For menu entries and everything else
+    pub small: Font,
+}
+
+impl Fonts {
+    pub fn load(ctx: &mut Context) -> Self {
+        let load = |ctx: &mut Context, size| {
+            Font::from_vector_file_data(
+                ctx,
+                resources::BITPOTION_FONT,
+                size,
+            )
+            .expect("Failed to instantiate font")
+        };
+
+        Self {
+            large: load(ctx, 64.0),
+            small: load(ctx, 32.0),
+        }
+    }
+}
+
+/// What every scene has access to, so that switching scenes
+/// never loads anything into GPU memory again
+pub struct Shared {
+    /// The active screen scaler
+    pub scaler: ScreenScaler,
+    /// Holds every texture of the game
+    pub renderer: Renderer,
+    pub fonts: Fonts,
+    /// The sprite every menu is drawn over
+    pub panel: Panel,
+    pub sound: SoundManager,
+    pub score_table: ScoreTable,
+    /// The waves every run goes through
+    pub waves: Rc<Waves>,
+    /// The difficulty new runs are played on
+    pub difficulty: Difficulty,
+    /// Real time, used for timers that must keep running even
+    /// when the game clock is paused
+    pub ui_clock: GameClock,
+    /// The gamepad the player may be using
+    pub gamepads: Gamepads,
+    /// Whether runs can be changed from the developer console
+    pub console_enabled: bool,
+    /// The player's settings, saved whenever they leave the
+    /// settings screen
+    pub config: Config,
+}
+
+/// What should happen to the scene stack after a scene was
+/// updated
+pub enum Transition {
+    /// Stay on the current scene
+    None,
+    /// Put a new scene on top of the current one, which is
+    /// kept around until the new one is popped
+    Push(Box<dyn Scene>),
+    /// Go back to the scene below the current one
+    Pop,
+    /// Throw every scene away and start over from a new one
+    Switch(Box<dyn Scene>),
+    /// Close the game
+    Quit,
+}
+
+/// A screen of the game, such as the title screen or a run
+/// being played.
+///
+/// Scenes are kept in a stack by
+/// [`GameState`](crate::gamestate::GameState), where only the
+/// one on top is updated.
+pub trait Scene {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition>;
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result;
+
+    fn event(
+        &mut self,
+        _ctx: &mut Context,
+        _shared: &mut Shared,
+        _event: Event,
+    ) -> tetra::Result<Transition> {
+        Ok(Transition::None)
+    }
+
+    /// Called when the scene is back on top of the stack,
+    /// after the one pushed over it was popped
+    fn on_resume(&mut self, _shared: &mut Shared) {}
+
+    /// Whether the scene below this one should still be drawn
+    /// behind it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Whether the music should be turned down while the scene
+    /// is on the stack
+    fn ducks_music(&self) -> bool {
+        false
+    }
+
+    /// Whether the mouse cursor should be hidden while the
+    /// scene is on top, because it draws its own
+    fn hides_cursor(&self) -> bool {
+        false
+    }
+
+    /// The run this scene shows, if any, for the debug overlay
+    /// to draw over
+    fn simulation(&self) -> Option<&Simulation> {
+        None
+    }
+}
@@ -0,0 +1,552 @@
+//! The table of the best runs played on this machine.
+//!
+//! It's kept in a plain text file in the
+//! [data directory](crate::paths::data_dir). The first line
+//! holds a header with the version of the format, and every
+//! other line holds a run, with its fields separated by tabs:
+//!
+//! ```text
+//! endless-trial high scores v2
+//! <difficulty> <score> <wave> <duration in ms> <seed> <unix time> <name>
+//! ```
+//!
+//! Each difficulty keeps a table of its own. Version 1 files
+//! have no difficulty field, and their runs count as played on
+//! normal.
+//!
+//! Lines that can't be read are skipped, so that a damaged
+//! file loses as few runs as possible.
+
+use std::{
+    cmp::Reverse,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{difficulty::Difficulty, paths};
+
+/// How many runs the table of each difficulty keeps
+pub const MAX_ENTRIES: usize = 10;
+
+/// The longest name a run can be saved with
+pub const MAX_NAME_LEN: usize = 12;
+
+const FILE_NAME: &str = "highscores.txt";
+const HEADER_PREFIX: &str = "endless-trial high scores v";
+const VERSION: u32 = 2;
+
+/// A run that made it into the table
+#[derive(Debug, Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    /// The wave the run ended at, starting from one
+    pub wave: u16,
+    /// How much game time the run lasted
+    pub duration: Duration,
+    pub seed: u64,
+    /// When the run ended, in seconds since the Unix epoch
+    pub date: u64,
+    pub difficulty: Difficulty,
+}
+
+impl HighScore {
+    /// A run that ended just now, yet to be named
+    pub fn new(
+        score: u64,
+        wave: u16,
+        duration: Duration,
+        seed: u64,
+        difficulty: Difficulty,
+    ) -> Self {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+
+        Self {
+            name: String::new(),
+            score,
+            wave,
+            duration,
+            seed,
+            date,
+            difficulty,
+        }
+    }
+
+    /// Reads a line of a file of the given version
+    fn parse(line: &str, version: u32) -> Option<Self> {
+        let field_count = if version >= 2 { 7 } else { 6 };
+        let mut fields = line.splitn(field_count, '\t');
+        let mut next = || fields.next();
+
+        let difficulty = if version >= 2 {
+            Difficulty::from_name(next()?)?
+        } else {
+            Difficulty::Normal
+        };
+
+        Some(Self {
+            difficulty,
+            score: next()?.parse().ok()?,
+            wave: next()?.parse().ok()?,
+            duration: Duration::from_millis(
+                next()?.parse().ok()?,
+            ),
+            seed: next()?.parse().ok()?,
+            date: next()?.parse().ok()?,
+            name: sanitize_name(next()?),
+        })
+    }
+
+    /// The date the run ended at, as `YYYY-MM-DD` in UTC
+    pub fn date_string(&self) -> String {
+        let (year, month, day) =
+            civil_from_days((self.date / 86400) as i64);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+impl fmt::Display for HighScore {
+    /// Formats the run as a line of the high score file
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.difficulty.name(),
+            self.score,
+            self.wave,
+            self.duration.as_millis(),
+            self.seed,
+            self.date,
+            self.name
+        )
+    }
+}
+
+/// Keeps a name on a single line of the file, and short
+/// enough to fit on the screen
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LEN)
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+/// Turns days since the Unix epoch into a (year, month, day)
+/// date, from Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe =
+        (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    (year, month, day)
+}
+
+/// Keeps the best [`MAX_ENTRIES`] runs of every difficulty,
+/// which must already be sorted by score
+fn truncate(entries: &mut Vec<HighScore>) {
+    let mut kept = [0; Difficulty::ALL.len()];
+    entries.retain(|entry| {
+        let kept = &mut kept[entry.difficulty as usize];
+        *kept += 1;
+        *kept <= MAX_ENTRIES
+    });
+}
+
+/// The best runs of every difficulty
+pub struct ScoreTable {
+    /// From the highest score to the lowest
+    entries: Vec<HighScore>,
+    /// Where the table is saved, if we know where to keep it
+    path: Option<PathBuf>,
+}
+
+impl ScoreTable {
+    /// Loads the table from the data directory, starting with
+    /// an empty one if there's none yet or it can't be read
+    pub fn load() -> Self {
+        let Some(path) =
+            paths::data_dir().map(|dir| dir.join(FILE_NAME))
+        else {
+            eprintln!(
+                "[LOG] No data directory found, high scores won't be saved"
+            );
+            return Self {
+                entries: Vec::new(),
+                path: None,
+            };
+        };
+
+        Self::at(path)
+    }
+
+    /// The table kept at `path`. One saved by a newer version
+    /// of the game is left as it is, and nothing is saved over
+    /// it
+    fn at(path: PathBuf) -> Self {
+        match Self::read(&path) {
+            Some(entries) => Self {
+                entries,
+                path: Some(path),
+            },
+            None => Self {
+                entries: Vec::new(),
+                path: None,
+            },
+        }
+    }
+
+    /// Returns `None` if the table is from a newer version of
+    /// the game
+    fn read(path: &Path) -> Option<Vec<HighScore>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err)
+                if err.kind() == io::ErrorKind::NotFound =>
+            {
+                return Some(Vec::new())
+            }
+            Err(err) => {
+                eprintln!(
+                    "[LOG] Failed to read {}: {err}",
+                    path.display()
+                );
+                return Some(Vec::new());
+            }
+        };
+
+        let mut lines = contents.lines();
+        let version: Option<u32> = lines
+            .next()
+            .and_then(|header| {
+                header.strip_prefix(HEADER_PREFIX)
+            })
+            .and_then(|version| version.parse().ok());
+
+        if let Some(version) =
+            version.filter(|&version| version > VERSION)
+        {
+            eprintln!(
+                "[LOG] {} is from a newer version of the game (version {version}), so high scores won't be saved",
+                path.display()
+            );
+            return None;
+        }
+
+        let Some(version) = version
+            .filter(|version| (1..=VERSION).contains(version))
+        else {
+            let backup = paths::move_aside(path);
+            eprintln!(
+                "[LOG] {} is not a high score table we can read, moved it to {}",
+                path.display(),
+                backup.display()
+            );
+
+            return Some(Vec::new());
+        };
+
+        let mut entries: Vec<HighScore> = lines
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                let entry = HighScore::parse(line, version);
+                if entry.is_none() {
+                    eprintln!(
+                        "[LOG] Skipping a damaged high score: {line:?}"
+                    );
+                }
+                entry
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| Reverse(entry.score));
+        truncate(&mut entries);
+
+        Some(entries)
+    }
+
+    /// The runs played on `difficulty`, from the highest score
+    /// to the lowest
+    pub fn entries(
+        &self,
+        difficulty: Difficulty,
+    ) -> impl Iterator<Item = &HighScore> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.difficulty == difficulty)
+    }
+
+    /// Whether a run with this score would make it into the
+    /// table of its difficulty
+    pub fn qualifies(
+        &self,
+        score: u64,
+        difficulty: Difficulty,
+    ) -> bool {
+        let (count, lowest) = self
+            .entries(difficulty)
+            .fold((0, None), |(count, _), entry| {
+                (count + 1, Some(entry.score))
+            });
+
+        score > 0
+            && (count < MAX_ENTRIES
+                || lowest.is_some_and(|lowest| score > lowest))
+    }
+
+    /// Adds a run to the table and saves it
+    pub fn insert(&mut self, entry: HighScore) {
+        // Ties go to the run that got there first
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(rank, entry);
+        truncate(&mut self.entries);
+
+        if let Err(err) = self.save() {
+            eprintln!(
+                "[LOG] Failed to save the high scores: {err}"
+            );
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut contents = format!("{HEADER_PREFIX}{VERSION}\n");
+        for entry in &self.entries {
+            contents.push_str(&format!("{entry}\n"));
+        }
+
+        paths::write_atomically(path, &contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::TempDir;
+
+    fn run(score: u64, difficulty: Difficulty) -> HighScore {
+        HighScore {
+            name: format!("Run {score}"),
+            score,
+            wave: 3,
+            duration: Duration::from_millis(65_432),
+            seed: score * 7,
+            date: 1_700_000_000,
+            difficulty,
+        }
+    }
+
+    fn scores(
+        table: &ScoreTable,
+        difficulty: Difficulty,
+    ) -> Vec<u64> {
+        table
+            .entries(difficulty)
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn reads_version_2_files() {
+        let dir = TempDir::new("scores-v2");
+        let path = dir.file(FILE_NAME,
+            "endless-trial high scores v2\n\
+             Hard\t300\t4\t90000\t11\t1700000000\tAda\n\
+             Normal\t500\t6\t120000\t22\t1700000100\tGrace Hopper\n\
+             \n\
+             Normal\tnot a score\t1\t1\t1\t1\tBroken\n\
+             Easy\t100\t2\t30000\t33\t1700000200\tLin\n",
+        );
+
+        let table = ScoreTable::at(path.clone());
+
+        assert_eq!(table.entries.len(), 3);
+        assert_eq!(scores(&table, Difficulty::Normal), [500]);
+        assert_eq!(scores(&table, Difficulty::Hard), [300]);
+        assert_eq!(scores(&table, Difficulty::Easy), [100]);
+
+        let grace =
+            table.entries(Difficulty::Normal).next().unwrap();
+        assert_eq!(grace.name, "Grace Hopper");
+        assert_eq!(grace.wave, 6);
+        assert_eq!(grace.duration, Duration::from_secs(120));
+        assert_eq!(grace.seed, 22);
+        assert_eq!(grace.date, 1_700_000_100);
+    }
+
+    #[test]
+    fn migrates_version_1_files() {
+        let dir = TempDir::new("scores-v1");
+        let path = dir.file(
+            FILE_NAME,
+            "endless-trial high scores v1\n\
+             200\t3\t60000\t5\t1600000000\tOld run\n\
+             400\t5\t90000\t6\t1600000100\tOlder run\n",
+        );
+
+        let mut table = ScoreTable::at(path.clone());
+        assert_eq!(
+            scores(&table, Difficulty::Normal),
+            [400, 200]
+        );
+
+        // Saving writes the table back as the current version
+        table.insert(run(300, Difficulty::Hard));
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents
+            .starts_with("endless-trial high scores v2\n"));
+
+        let reread = ScoreTable::at(path.clone());
+
+        assert_eq!(
+            scores(&reread, Difficulty::Normal),
+            [400, 200]
+        );
+        assert_eq!(scores(&reread, Difficulty::Hard), [300]);
+        assert_eq!(
+            reread
+                .entries(Difficulty::Normal)
+                .next()
+                .unwrap()
+                .name,
+            "Older run"
+        );
+    }
+
+    #[test]
+    fn keeps_the_best_runs_of_every_difficulty() {
+        let dir = TempDir::new("scores-top");
+        let path = dir
+            .file(FILE_NAME, "endless-trial high scores v2\n");
+        let mut table = ScoreTable::at(path.clone());
+
+        for score in 1..=15 {
+            table.insert(run(score * 10, Difficulty::Normal));
+        }
+        for score in [5, 1, 3] {
+            table.insert(run(score, Difficulty::Nightmare));
+        }
+
+        let expected: Vec<u64> =
+            (6..=15).rev().map(|score| score * 10).collect();
+        assert_eq!(scores(&table, Difficulty::Normal), expected);
+        assert_eq!(
+            scores(&table, Difficulty::Nightmare),
+            [5, 3, 1]
+        );
+
+        // A full table only takes runs better than its worst
+        assert!(!table.qualifies(60, Difficulty::Normal));
+        assert!(table.qualifies(61, Difficulty::Normal));
+        assert!(table.qualifies(1, Difficulty::Hard));
+        assert!(!table.qualifies(0, Difficulty::Hard));
+
+        // The saved table reads back the same
+        let reread = ScoreTable::at(path.clone());
+
+        assert_eq!(
+            scores(&reread, Difficulty::Normal),
+            expected
+        );
+        assert_eq!(
+            scores(&reread, Difficulty::Nightmare),
+            [5, 3, 1]
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_run() {
+        let dir = TempDir::new("scores-ties");
+        let path = dir
+            .file(FILE_NAME, "endless-trial high scores v2\n");
+        let mut table = ScoreTable::at(path.clone());
+
+        let mut first = run(100, Difficulty::Normal);
+        first.name = "First".to_owned();
+        let mut second = run(100, Difficulty::Normal);
+        second.name = "Second".to_owned();
+        table.insert(first);
+        table.insert(second);
+
+        let names: Vec<_> = table
+            .entries(Difficulty::Normal)
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["First", "Second"]);
+    }
+
+    #[test]
+    fn unreadable_files_are_moved_aside() {
+        let dir = TempDir::new("scores-unreadable");
+        let path =
+            dir.file(FILE_NAME, "something else entirely\n");
+
+        let table = ScoreTable::at(path.clone());
+        let backup = dir.join("highscores.txt.bak");
+
+        assert!(table.entries.is_empty());
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(&backup).unwrap(),
+            "something else entirely\n"
+        );
+    }
+
+    #[test]
+    fn newer_tables_are_left_alone() {
+        let dir = TempDir::new("scores-newer");
+        let contents =
+            "endless-trial high scores v3\nsomething new\n";
+        let path = dir.file(FILE_NAME, contents);
+
+        let mut table = ScoreTable::at(path.clone());
+        assert!(table.entries.is_empty());
+
+        table.insert(run(100, Difficulty::Normal));
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        assert!(!dir.join("highscores.txt.bak").exists());
+    }
+
+    #[test]
+    fn names_stay_on_one_short_line() {
+        assert_eq!(
+            sanitize_name("Ada\tLove\nlace"),
+            "AdaLovelace"
+        );
+        assert_eq!(sanitize_name("  Ada  "), "Ada");
+        assert_eq!(
+            sanitize_name("a much too long name"),
+            "a much too l"
+        );
+    }
+
+    #[test]
+    fn dates_are_formatted_in_utc() {
+        let mut entry = run(1, Difficulty::Normal);
+
+        entry.date = 0;
+        assert_eq!(entry.date_string(), "1970-01-01");
+        entry.date = 951_825_600;
+        assert_eq!(entry.date_string(), "2000-02-29");
+    }
+}
@@ -0,0 +1,389 @@
+use tetra::{
+    graphics::scaling::ScalingMode,
+    input::{self, Key},
+    window, Context,
+};
+
+use crate::{
+    controls::Controls,
+    difficulty::Difficulty,
+    gamestate::next_scaling_mode,
+    input::AimMode,
+    menu::Menu,
+    scene::{Scene, Shared, Transition},
+    sound::AudioSettings,
+};
+
+/// The entries of the settings screen
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsEntry {
+    Difficulty,
+    ScalingMode,
+    WindowSize,
+    Fullscreen,
+    VSync,
+    Aim,
+    Controls,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    Sound,
+    Back,
+}
+
+/// How much a volume changes with each press, in tenths
+const VOLUME_STEPS: i32 = 10;
+
+/// The window sizes the player can pick from, smallest first
+const WINDOW_SIZES: [(i32, i32); 4] =
+    [(600, 600), (800, 800), (1000, 1000), (1200, 1200)];
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("Difficulty: {}", difficulty.name())
+}
+
+fn scaling_mode_label(mode: ScalingMode) -> String {
+    format!("Scaling: {mode:?}")
+}
+
+fn window_size_label((width, height): (i32, i32)) -> String {
+    format!("Window: {width}x{height}")
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn fullscreen_label(fullscreen: bool) -> String {
+    format!("Fullscreen: {}", on_off(fullscreen))
+}
+
+fn vsync_label(vsync: bool) -> String {
+    format!("VSync: {}", on_off(vsync))
+}
+
+/// The first window size bigger than `size`, going back to the
+/// smallest one after the biggest
+fn next_window_size((width, _): (i32, i32)) -> (i32, i32) {
+    WINDOW_SIZES
+        .iter()
+        .copied()
+        .find(|&(other, _)| other > width)
+        .unwrap_or(WINDOW_SIZES[0])
+}
+
+fn aim_label(aim: AimMode) -> String {
+    format!("Aim: {}", aim.name())
+}
+
+/// The volume an entry changes, along with its name
+fn volume_mut(
+    audio: &mut AudioSettings,
+    entry: SettingsEntry,
+) -> Option<(&mut f32, &'static str)> {
+    match entry {
+        SettingsEntry::MasterVolume => {
+            Some((&mut audio.master_volume, "Master volume"))
+        }
+        SettingsEntry::MusicVolume => {
+            Some((&mut audio.music_volume, "Music volume"))
+        }
+        SettingsEntry::EffectsVolume => {
+            Some((&mut audio.effects_volume, "Effects volume"))
+        }
+        _ => None,
+    }
+}
+
+fn volume_label(name: &str, volume: f32) -> String {
+    format!("{name}: {}%", (volume * 100.0).round())
+}
+
+fn sound_label(muted: bool) -> String {
+    format!("Sound: {}", on_off(!muted))
+}
+
+/// Moves a volume by `steps` tenths, wrapping around past
+/// either end if `wrap` is set and stopping there otherwise
+fn step_volume(volume: f32, steps: i32, wrap: bool) -> f32 {
+    let step = (volume * VOLUME_STEPS as f32).round() as i32;
+    let next = if wrap {
+        (step + steps).rem_euclid(VOLUME_STEPS + 1)
+    } else {
+        (step + steps).clamp(0, VOLUME_STEPS)
+    };
+
+    next as f32 / VOLUME_STEPS as f32
+}
+
+/// Lets the player pick the difficulty and how to aim, and
+/// change the window, how the game is drawn and how loud it
+/// is. Everything applies right away, and the settings are
+/// saved to the config file once the player leaves. Volumes
+/// are changed with Left/Right
+pub struct Settings {
+    menu: Menu<SettingsEntry>,
+}
+
+impl Settings {
+    pub fn new(ctx: &mut Context, shared: &Shared) -> Self {
+        let audio = shared.config.audio;
+        let window = shared.config.window;
+
+        Self {
+            menu: Menu::new(
+                ctx,
+                shared,
+                "Settings",
+                &[
+                    (
+                        SettingsEntry::Difficulty,
+                        &difficulty_label(shared.difficulty),
+                    ),
+                    (
+                        SettingsEntry::ScalingMode,
+                        &scaling_mode_label(
+                            shared.scaler.mode(),
+                        ),
+                    ),
+                    (
+                        SettingsEntry::WindowSize,
+                        &window_size_label(window::get_size(
+                            ctx,
+                        )),
+                    ),
+                    (
+                        SettingsEntry::Fullscreen,
+                        &fullscreen_label(
+                            window::is_fullscreen(ctx),
+                        ),
+                    ),
+                    (
+                        SettingsEntry::VSync,
+                        &vsync_label(window.vsync),
+                    ),
+                    (
+                        SettingsEntry::Aim,
+                        &aim_label(shared.config.controls.aim),
+                    ),
+                    (SettingsEntry::Controls, "Controls"),
+                    (
+                        SettingsEntry::MasterVolume,
+                        &volume_label(
+                            "Master volume",
+                            audio.master_volume,
+                        ),
+                    ),
+                    (
+                        SettingsEntry::MusicVolume,
+                        &volume_label(
+                            "Music volume",
+                            audio.music_volume,
+                        ),
+                    ),
+                    (
+                        SettingsEntry::EffectsVolume,
+                        &volume_label(
+                            "Effects volume",
+                            audio.effects_volume,
+                        ),
+                    ),
+                    (
+                        SettingsEntry::Sound,
+                        &sound_label(audio.muted),
+                    ),
+                    (SettingsEntry::Back, "Back"),
+                ],
+            ),
+        }
+    }
+
+    /// Changes the audio setting of `entry`, if it has one, by
+    /// `steps` tenths
+    fn change_audio(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+        entry: SettingsEntry,
+        steps: i32,
+        wrap: bool,
+    ) {
+        let audio = &mut shared.config.audio;
+
+        let label = if entry == SettingsEntry::Sound {
+            audio.muted = !audio.muted;
+            sound_label(audio.muted)
+        } else if let Some((volume, name)) =
+            volume_mut(audio, entry)
+        {
+            *volume = step_volume(*volume, steps, wrap);
+            volume_label(name, *volume)
+        } else {
+            return;
+        };
+
+        self.menu.set_label(ctx, entry, &label);
+        shared.sound.apply_settings(ctx, shared.config.audio);
+    }
+}
+
+/// Saves the settings on the way out, along with the size the
+/// window was resized to
+fn leave(ctx: &Context, shared: &mut Shared) -> Transition {
+    let window = &mut shared.config.window;
+    if !window::is_fullscreen(ctx) {
+        (window.width, window.height) = window::get_size(ctx);
+    }
+
+    shared.config.save();
+    Transition::Pop
+}
+
+/// Logs a window setting that couldn't be applied
+fn log_window_error(result: tetra::Result) {
+    if let Err(err) = result {
+        eprintln!("[LOG] Failed to change the window: {err}");
+    }
+}
+
+impl Scene for Settings {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        if input::is_key_pressed(ctx, Key::Escape) {
+            return Ok(leave(ctx, shared));
+        }
+
+        for (keys, steps) in [
+            ([Key::Left, Key::A], -1),
+            ([Key::Right, Key::D], 1),
+        ] {
+            if keys
+                .iter()
+                .any(|&key| input::is_key_pressed(ctx, key))
+            {
+                let selected = self.menu.selected();
+                self.change_audio(
+                    ctx, shared, selected, steps, false,
+                );
+            }
+        }
+
+        let transition = match self.menu.update(ctx) {
+            Some(SettingsEntry::Difficulty) => {
+                shared.difficulty = shared.difficulty.next();
+                shared.config.difficulty = shared.difficulty;
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::Difficulty,
+                    &difficulty_label(shared.difficulty),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::ScalingMode) => {
+                let next =
+                    next_scaling_mode(shared.scaler.mode());
+                shared.scaler.set_mode(next);
+                shared.config.window.scaling = next;
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::ScalingMode,
+                    &scaling_mode_label(next),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::WindowSize) => {
+                let (width, height) =
+                    next_window_size(window::get_size(ctx));
+                log_window_error(window::set_size(
+                    ctx, width, height,
+                ));
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::WindowSize,
+                    &window_size_label((width, height)),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::Fullscreen) => {
+                let fullscreen = !window::is_fullscreen(ctx);
+                log_window_error(window::set_fullscreen(
+                    ctx, fullscreen,
+                ));
+                shared.config.window.fullscreen =
+                    window::is_fullscreen(ctx);
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::Fullscreen,
+                    &fullscreen_label(
+                        shared.config.window.fullscreen,
+                    ),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::VSync) => {
+                let window = &mut shared.config.window;
+                window.vsync = !window.vsync;
+                log_window_error(window::set_vsync(
+                    ctx,
+                    window.vsync,
+                ));
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::VSync,
+                    &vsync_label(window.vsync),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::Aim) => {
+                let controls = &mut shared.config.controls;
+                controls.aim = controls.aim.next();
+                self.menu.set_label(
+                    ctx,
+                    SettingsEntry::Aim,
+                    &aim_label(controls.aim),
+                );
+
+                Transition::None
+            }
+            Some(SettingsEntry::Controls) => Transition::Push(
+                Box::new(Controls::new(ctx, shared)),
+            ),
+            Some(
+                entry @ (SettingsEntry::MasterVolume
+                | SettingsEntry::MusicVolume
+                | SettingsEntry::EffectsVolume
+                | SettingsEntry::Sound),
+            ) => {
+                self.change_audio(ctx, shared, entry, 1, true);
+                Transition::None
+            }
+            Some(SettingsEntry::Back) => leave(ctx, shared),
+            None => Transition::None,
+        };
+
+        Ok(transition)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        shared.renderer.draw_background(ctx);
+        self.menu.draw(ctx);
+
+        Ok(())
+    }
+}
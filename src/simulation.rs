@@ -0,0 +1,486 @@
+use std::{rc::Rc, time::Duration};
+
+use rand::{prelude::SliceRandom, Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+
+use crate::{
+    clock::GameClock,
+    difficulty::Difficulty,
+    enemy::EnemyManager,
+    event::GameEvent,
+    humanoid::{Humanoid, HumanoidType},
+    input::InputFrame,
+    oneoffanim::OneOffAnimationManager,
+    player::PlayerManager,
+    powerup::PowerUpManager,
+    spatial::SpatialGrid,
+    timer::Timer,
+    timestep::TICK_DURATION,
+    waves::Waves,
+    HEIGHT, WIDTH,
+};
+
+/// The size of the cells of the grid enemies are looked up in
+/// for collisions, a few times the size of an enemy
+const COLLISION_CELL_SIZE: f32 = 64.0;
+
+/// The random number generator used by the game logic.
+///
+/// Unlike `SmallRng`, its algorithm is the same on every
+/// platform, so a seed always plays out the same way.
+pub type GameRng = Pcg64Mcg;
+
+/// The game logic, without any rendering or input polling.
+///
+/// Advances one tick at a time from an explicit
+/// [`InputFrame`], so it can run without a window. Two
+/// simulations built from the same seed and fed the same
+/// inputs always play out identically.
+pub struct Simulation {
+    /// The game time, only advanced by `tick`
+    clock: GameClock,
+    player_manager: PlayerManager,
+    power_up_mgr: PowerUpManager,
+    enemy_mgr: EnemyManager,
+    one_off_anim_mgr: OneOffAnimationManager,
+    /// The seed `rng` was created from
+    seed: u64,
+    rng: GameRng,
+    game_score: u64,
+    difficulty: Difficulty,
+    /// The waves of the run
+    waves: Rc<Waves>,
+    current_wave: u16,
+    /// Times how long the current wave lasts
+    wave_timer: Timer,
+    /// When the current wave's bosses spawn, in order
+    boss_schedule: Vec<Duration>,
+    /// How many of the current wave's bosses have spawned so far
+    bosses_spawned: usize,
+    /// Where every enemy is, rebuilt on every tick
+    enemy_grid: SpatialGrid,
+    /// What happened during the last tick
+    events: Vec<GameEvent>,
+}
+
+impl Simulation {
+    pub fn new(
+        seed: u64,
+        waves: Rc<Waves>,
+        difficulty: Difficulty,
+    ) -> Self {
+        let clock = GameClock::new();
+        let first_wave = waves.get(0);
+
+        Self {
+            player_manager: PlayerManager::new(
+                &clock, difficulty,
+            ),
+            power_up_mgr: PowerUpManager::new(
+                &clock,
+                first_wave
+                    .power_up_interval
+                    .mul_f64(difficulty.power_up_interval()),
+            ),
+            enemy_mgr: EnemyManager::new(
+                &clock,
+                first_wave.spawn_interval,
+                difficulty,
+            ),
+            one_off_anim_mgr: OneOffAnimationManager::new(
+                &clock,
+            ),
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            game_score: 0,
+            difficulty,
+            current_wave: 0,
+            wave_timer: Timer::start_now_with_interval(
+                &clock,
+                first_wave.duration,
+            ),
+            boss_schedule: first_wave.bosses.clone(),
+            bosses_spawned: 0,
+            enemy_grid: SpatialGrid::new(
+                WIDTH as f32,
+                HEIGHT as f32,
+                COLLISION_CELL_SIZE,
+            ),
+            events: Vec::new(),
+            waves,
+            clock,
+        }
+    }
+
+    pub fn clock(&self) -> &GameClock {
+        &self.clock
+    }
+
+    /// Gives access to the game clock, in order to pause the
+    /// game or change how fast it runs
+    pub fn clock_mut(&mut self) -> &mut GameClock {
+        &mut self.clock
+    }
+
+    pub fn player_manager(&self) -> &PlayerManager {
+        &self.player_manager
+    }
+
+    /// Gives access to the player, so that the console can
+    /// change them
+    pub fn player_mut(&mut self) -> &mut Humanoid {
+        self.player_manager.player_mut()
+    }
+
+    pub fn power_up_mgr(&self) -> &PowerUpManager {
+        &self.power_up_mgr
+    }
+
+    pub fn enemy_mgr(&self) -> &EnemyManager {
+        &self.enemy_mgr
+    }
+
+    pub fn one_off_anim_mgr(&self) -> &OneOffAnimationManager {
+        &self.one_off_anim_mgr
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn game_score(&self) -> u64 {
+        self.game_score
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// The current wave, starting from zero
+    pub fn current_wave(&self) -> u16 {
+        self.current_wave
+    }
+
+    /// How many waves past the last authored one this is, which
+    /// is zero until the endless waves start
+    pub fn difficulty_tier(&self) -> u16 {
+        (self.current_wave.saturating_add(1))
+            .saturating_sub(self.waves.list.len() as u16)
+    }
+
+    /// The waves this run goes through
+    pub fn waves(&self) -> Rc<Waves> {
+        Rc::clone(&self.waves)
+    }
+
+    /// Whether another wave comes after this one
+    fn has_next_wave(&self) -> bool {
+        self.waves.endless.is_some()
+            || (self.current_wave as usize) + 1
+                < self.waves.list.len()
+    }
+
+    /// How long until the next wave starts, or `None` if this
+    /// is the last one
+    pub fn wave_time_left(&self) -> Option<Duration> {
+        if !self.has_next_wave() {
+            return None;
+        }
+
+        Some(self.wave_timer.remaining(&self.clock))
+    }
+
+    /// What happened during the last tick, such as enemies
+    /// dying or the player firing
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.player_manager.is_player_dead()
+    }
+
+    fn check_for_wave_change(&mut self) {
+        if !self.wave_timer.is_ready(&self.clock)
+            || !self.has_next_wave()
+        {
+            return;
+        }
+
+        self.current_wave = self.current_wave.saturating_add(1);
+        self.start_wave();
+        self.events.push(GameEvent::WaveStarted);
+
+        match self.difficulty_tier() {
+            0 => println!(
+                "Commencing wave {}",
+                self.current_wave + 1
+            ),
+            tier => println!(
+                "Commencing wave {} (endless tier {tier})",
+                self.current_wave + 1
+            ),
+        }
+    }
+
+    /// Starts the wave at `index` right away, skipping or going
+    /// back to it
+    pub fn jump_to_wave(&mut self, index: u16) {
+        self.current_wave = index;
+        self.start_wave();
+    }
+
+    /// Whether the run has a wave at `index`
+    pub fn has_wave(&self, index: u16) -> bool {
+        self.waves.has_wave(index)
+    }
+
+    /// Spawns `count` enemies of the given kind right away, no
+    /// matter how many are already around
+    pub fn spawn_enemies_of(
+        &mut self,
+        kind: HumanoidType,
+        count: u32,
+    ) {
+        for _ in 0..count {
+            self.enemy_mgr.spawn_enemy(
+                kind,
+                &mut self.rng,
+                &self.clock,
+            );
+        }
+    }
+
+    /// Removes every enemy without scoring them, returning how
+    /// many there were
+    pub fn kill_all_enemies(&mut self) -> usize {
+        let count = self.enemy_mgr.enemies.len();
+        self.enemy_mgr.enemies.clear();
+
+        count
+    }
+
+    /// Sets everything up for the current wave to start now
+    fn start_wave(&mut self) {
+        let waves = Rc::clone(&self.waves);
+        let wave = waves.get(self.current_wave);
+        let tier = self.difficulty_tier();
+
+        let mut spawn_interval = wave.spawn_interval;
+        let mut boss_schedule = wave.bosses.clone();
+        let mut enemy_health = 1.0;
+        let mut projectile_speed = 1.0;
+
+        if let Some(endless) = waves.endless.as_ref() {
+            spawn_interval = spawn_interval
+                .div_f64(endless.spawn_rate.at(tier));
+            enemy_health = endless.enemy_health.at(tier);
+            projectile_speed = endless.projectile_speed.at(tier);
+
+            // Spread the extra bosses evenly across the wave
+            let extra_bosses = endless.bosses.at(tier) as u32;
+            boss_schedule.extend((1..=extra_bosses).map(
+                |boss| wave.duration * boss / (extra_bosses + 1),
+            ));
+            boss_schedule.sort();
+        }
+
+        self.wave_timer.set_interval(wave.duration);
+        self.wave_timer.reset(&self.clock);
+        self.enemy_mgr.set_spawn_interval(spawn_interval);
+        self.enemy_mgr
+            .set_difficulty(enemy_health, projectile_speed);
+        self.power_up_mgr.set_spawn_interval(
+            wave.power_up_interval
+                .mul_f64(self.difficulty.power_up_interval()),
+        );
+        self.boss_schedule = boss_schedule;
+        self.bosses_spawned = 0;
+    }
+
+    /// The kind of enemy a spawned one of `kind` becomes, which
+    /// may be the next kind up on endless tiers
+    fn promote_to_elite(
+        &mut self,
+        kind: HumanoidType,
+    ) -> HumanoidType {
+        let chance = match &self.waves.endless {
+            Some(endless) => {
+                endless.elite_chance.at(self.difficulty_tier())
+            }
+            None => 0.0,
+        };
+
+        // Don't touch the RNG unless needed, so that the
+        // authored waves always play out the same
+        if chance <= 0.0 || !self.rng.gen_bool(chance) {
+            return kind;
+        }
+
+        match kind {
+            HumanoidType::BasicEnemy => {
+                HumanoidType::StrongerEnemy
+            }
+            HumanoidType::StrongerEnemy => {
+                HumanoidType::BadassEnemy
+            }
+            other => other,
+        }
+    }
+
+    fn spawn_enemies(&mut self) {
+        // Scripted bosses spawn no matter how many enemies are
+        // already around
+        let wave_elapsed = self.wave_timer.elapsed(&self.clock);
+        while self
+            .boss_schedule
+            .get(self.bosses_spawned)
+            .is_some_and(|&at| wave_elapsed >= at)
+        {
+            self.bosses_spawned += 1;
+            self.enemy_mgr.spawn_enemy(
+                HumanoidType::Boss,
+                &mut self.rng,
+                &self.clock,
+            );
+        }
+
+        let waves = Rc::clone(&self.waves);
+        let wave = waves.get(self.current_wave);
+
+        if self.enemy_mgr.can_spawn(&self.clock)
+            && wave.max_enemies.is_none_or(|max| {
+                self.enemy_mgr.enemies.len() < max
+            })
+        {
+            let kind = wave
+                .enemies
+                .choose_weighted(&mut self.rng, |x| x.1)
+                .expect("waves are validated to have enemies")
+                .0;
+            let kind = self.promote_to_elite(kind);
+
+            self.enemy_mgr.spawn_enemy(
+                kind,
+                &mut self.rng,
+                &self.clock,
+            );
+        }
+    }
+
+    /// Advances the game by a single tick, which moves the game
+    /// clock forward by [`TICK_DURATION`] (scaled by its time
+    /// scale)
+    pub fn tick(&mut self, input: &InputFrame) {
+        self.events.clear();
+
+        // Freeze the game logic if the game is over
+        if self.is_game_over() {
+            return;
+        }
+
+        // Nothing moves while the clock is paused
+        if self.clock.is_paused() {
+            return;
+        }
+
+        self.clock.advance(TICK_DURATION);
+        let hearts = self.player_manager.hearts();
+
+        // Checks if the current wave is over
+        self.check_for_wave_change();
+
+        // Every collision with an enemy is looked up in this
+        // grid, so it's built once per tick
+        self.enemy_grid.rebuild(
+            self.enemy_mgr
+                .enemies
+                .iter()
+                .map(Humanoid::collider),
+        );
+
+        // Check if the player collided with an enemy
+        let player_collider =
+            self.player_manager.player().collider();
+        if self.enemy_grid.any_intersecting(&player_collider) {
+            self.player_manager.register_hit();
+        }
+
+        // Check if an enemy was hit with a fireball from the
+        // player
+        self.enemy_mgr.check_for_fireball_collisions(
+            &self.enemy_grid,
+            self.player_manager.fireball_colliders(),
+            &mut self.one_off_anim_mgr,
+            &self.clock,
+            &mut self.events,
+        );
+
+        // Check if the player was hit with a cannonball from an
+        // enemy
+        self.enemy_mgr.check_for_cannonball_collisions(
+            self.player_manager.player_mut(),
+            &mut self.one_off_anim_mgr,
+            &self.clock,
+            &mut self.events,
+        );
+
+        // Check if any enemy got a power-up
+        self.power_up_mgr.check_for_enemy_collisions(
+            &self.enemy_grid,
+            &mut self.enemy_mgr.enemies,
+            &self.clock,
+        );
+
+        self.power_up_mgr.advance(
+            &mut self.rng,
+            self.player_manager.player_mut(),
+            &self.clock,
+            &mut self.events,
+        );
+
+        let active_power_ups =
+            self.player_manager.player().power_ups.len();
+        self.player_manager.update(
+            input,
+            &self.clock,
+            &mut self.events,
+        );
+        if self.player_manager.player().power_ups.len()
+            < active_power_ups
+        {
+            self.events.push(GameEvent::PowerUpExpired);
+        }
+
+        self.spawn_enemies();
+
+        // Calculate the enemy score now that new enemies have
+        // been spawned
+        let enemy_score = self.enemy_mgr.calc_score();
+
+        self.one_off_anim_mgr.update(&self.clock);
+
+        self.enemy_mgr.update(
+            self.player_manager.player_position(),
+            &self.clock,
+            &mut self.events,
+        );
+
+        self.power_up_mgr.update(&self.clock);
+
+        // If the game score has decreased then enemies have been
+        // killed, which adds to the game score
+        let killed_score =
+            enemy_score - self.enemy_mgr.calc_score();
+        self.game_score += (killed_score as f64
+            * self.difficulty.score_multiplier())
+        .round() as u64;
+
+        if self.player_manager.hearts() < hearts {
+            self.events.push(GameEvent::PlayerHurt);
+        }
+        if self.is_game_over() {
+            self.events.push(GameEvent::GameOver);
+        }
+    }
+}
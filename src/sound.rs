@@ -1,3 +1,359 @@
-// pub struct SoundManager {
-//     power_up_sound: 
-// }
\ No newline at end of file
+//! Sound effects and music.
+//!
+//! Every [`GameEvent`] has a sound effect, which is limited in
+//! how many copies of it can play at once and in how soon it
+//! can be played again, so that things like triple shots
+//! don't pile up into noise.
+//!
+//! How loud everything is comes from the player's
+//! [`AudioSettings`], and the music is turned down while the
+//! game is paused or over.
+//!
+//! Without the `audio` feature the [`SoundManager`] plays
+//! nothing.
+
+use std::time::Duration;
+
+#[cfg(feature = "audio")]
+use tetra::audio::{self, Sound, SoundInstance, SoundState};
+use tetra::Context;
+
+use crate::event::GameEvent;
+#[cfg(feature = "audio")]
+use crate::resources;
+
+#[cfg(feature = "audio")]
+const MUSIC_VOLUME: f32 = 0.35;
+/// How much of its volume the music keeps while it's ducked
+#[cfg(feature = "audio")]
+const DUCKED_MUSIC: f32 = 0.3;
+
+/// How loud the game is, as the player set it. Volumes go from
+/// 0 to 1
+#[derive(Debug, Clone, Copy)]
+pub struct AudioSettings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.7,
+            effects_volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+/// How a sound effect is played
+struct EffectConfig {
+    event: GameEvent,
+    data: &'static [u8],
+    volume: f32,
+    /// How many copies of the effect can play at once
+    max_voices: usize,
+    /// How long to wait before playing the effect again
+    cooldown: Duration,
+}
+
+#[cfg(feature = "audio")]
+const fn effect(
+    event: GameEvent,
+    data: &'static [u8],
+    volume: f32,
+    max_voices: usize,
+    cooldown_ms: u64,
+) -> EffectConfig {
+    EffectConfig {
+        event,
+        data,
+        volume,
+        max_voices,
+        cooldown: Duration::from_millis(cooldown_ms),
+    }
+}
+
+#[cfg(feature = "audio")]
+const EFFECTS: [EffectConfig; 10] = [
+    effect(
+        GameEvent::PlayerFired,
+        resources::FIRE_SOUND,
+        0.5,
+        3,
+        60,
+    ),
+    effect(
+        GameEvent::EnemyFired,
+        resources::ENEMY_FIRE_SOUND,
+        0.35,
+        4,
+        50,
+    ),
+    effect(
+        GameEvent::EnemyHit,
+        resources::ENEMY_HIT_SOUND,
+        0.5,
+        3,
+        40,
+    ),
+    effect(
+        GameEvent::EnemyDied,
+        resources::ENEMY_DEATH_SOUND,
+        0.6,
+        4,
+        40,
+    ),
+    effect(
+        GameEvent::PlayerHurt,
+        resources::PLAYER_HURT_SOUND,
+        0.8,
+        1,
+        200,
+    ),
+    effect(
+        GameEvent::CannonballImpact,
+        resources::CANNONBALL_IMPACT_SOUND,
+        0.6,
+        2,
+        100,
+    ),
+    effect(
+        GameEvent::PowerUpPickedUp,
+        resources::POWER_UP_SOUND,
+        0.7,
+        2,
+        0,
+    ),
+    effect(
+        GameEvent::PowerUpExpired,
+        resources::POWER_UP_EXPIRED_SOUND,
+        0.6,
+        2,
+        0,
+    ),
+    effect(
+        GameEvent::WaveStarted,
+        resources::WAVE_SOUND,
+        0.7,
+        1,
+        0,
+    ),
+    effect(
+        GameEvent::GameOver,
+        resources::GAME_OVER_SOUND,
+        0.8,
+        1,
+        0,
+    ),
+];
+
+#[cfg(feature = "audio")]
+struct Effect {
+    config: &'static EffectConfig,
+    sound: Sound,
+    /// The copies of the effect that may still be playing
+    voices: Vec<SoundInstance>,
+    /// When the effect was last played, in real time
+    last_played: Option<Duration>,
+}
+
+#[cfg(feature = "audio")]
+impl Effect {
+    /// Whether another copy of the effect can start at `now`
+    fn can_play(&mut self, now: Duration) -> bool {
+        if self.last_played.is_some_and(|last_played| {
+            now.saturating_sub(last_played)
+                < self.config.cooldown
+        }) {
+            return false;
+        }
+
+        self.voices.retain(|voice| {
+            voice.state() == SoundState::Playing
+        });
+
+        self.voices.len() < self.config.max_voices
+    }
+}
+
+#[cfg(feature = "audio")]
+pub struct SoundManager {
+    effects: Vec<Effect>,
+    music: Sound,
+    /// The music, once it started playing
+    music_instance: Option<SoundInstance>,
+    settings: AudioSettings,
+    /// Whether the music is turned down
+    is_ducked: bool,
+    /// Cleared once playing something fails, which usually
+    /// means there's no audio device to play on
+    is_available: bool,
+}
+
+#[cfg(feature = "audio")]
+impl SoundManager {
+    pub fn new() -> Self {
+        let effects = EFFECTS
+            .iter()
+            .map(|config| Effect {
+                config,
+                sound: Sound::from_encoded(config.data),
+                voices: Vec::new(),
+                last_played: None,
+            })
+            .collect();
+
+        Self {
+            effects,
+            music: Sound::from_encoded(resources::MUSIC),
+            music_instance: None,
+            settings: AudioSettings::default(),
+            is_ducked: false,
+            is_available: true,
+        }
+    }
+
+    /// Changes how loud everything is, including what's
+    /// already playing
+    pub fn apply_settings(
+        &mut self,
+        ctx: &mut Context,
+        settings: AudioSettings,
+    ) {
+        self.settings = settings;
+
+        let master_volume = if settings.muted {
+            0.0
+        } else {
+            settings.master_volume
+        };
+        audio::set_master_volume(ctx, master_volume);
+        self.update_music_volume();
+    }
+
+    /// Turns the music down, or back up
+    pub fn set_ducked(&mut self, is_ducked: bool) {
+        if self.is_ducked != is_ducked {
+            self.is_ducked = is_ducked;
+            self.update_music_volume();
+        }
+    }
+
+    fn music_volume(&self) -> f32 {
+        let ducking =
+            if self.is_ducked { DUCKED_MUSIC } else { 1.0 };
+
+        MUSIC_VOLUME * self.settings.music_volume * ducking
+    }
+
+    fn update_music_volume(&self) {
+        if let Some(music) = &self.music_instance {
+            music.set_volume(self.music_volume());
+        }
+    }
+
+    /// Starts looping the background music, if it isn't yet
+    pub fn start_music(&mut self, ctx: &Context) {
+        if self.music_instance.is_some() || !self.is_available {
+            return;
+        }
+
+        match self.music.repeat_with(
+            ctx,
+            self.music_volume(),
+            1.0,
+        ) {
+            Ok(instance) => self.music_instance = Some(instance),
+            Err(err) => self.disable(err),
+        }
+    }
+
+    /// Plays the effect of every event, where `now` is the
+    /// current real time
+    pub fn play_events(
+        &mut self,
+        ctx: &Context,
+        events: &[GameEvent],
+        now: Duration,
+    ) {
+        for &event in events {
+            self.play(ctx, event, now);
+        }
+    }
+
+    pub fn play(
+        &mut self,
+        ctx: &Context,
+        event: GameEvent,
+        now: Duration,
+    ) {
+        if !self.is_available {
+            return;
+        }
+
+        let Some(effect) = self
+            .effects
+            .iter_mut()
+            .find(|effect| effect.config.event == event)
+        else {
+            return;
+        };
+
+        if !effect.can_play(now) {
+            return;
+        }
+
+        match effect.sound.play_with(
+            ctx,
+            effect.config.volume * self.settings.effects_volume,
+            1.0,
+        ) {
+            Ok(voice) => {
+                effect.voices.push(voice);
+                effect.last_played = Some(now);
+            }
+            Err(err) => self.disable(err),
+        }
+    }
+
+    fn disable(&mut self, err: tetra::TetraError) {
+        eprintln!("[LOG] Disabled sound: {err}");
+        self.is_available = false;
+    }
+}
+
+/// Stands in for the sound manager when the game is built
+/// without audio
+#[cfg(not(feature = "audio"))]
+pub struct SoundManager;
+
+#[cfg(not(feature = "audio"))]
+impl SoundManager {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn apply_settings(
+        &mut self,
+        _ctx: &mut Context,
+        _settings: AudioSettings,
+    ) {
+    }
+
+    pub fn set_ducked(&mut self, _is_ducked: bool) {}
+
+    pub fn start_music(&mut self, _ctx: &Context) {}
+
+    pub fn play_events(
+        &mut self,
+        _ctx: &Context,
+        _events: &[GameEvent],
+        _now: Duration,
+    ) {
+    }
+}
@@ -0,0 +1,265 @@
+//! A uniform grid over the arena, used to find which bodies a
+//! collider overlaps without checking every single one.
+//!
+//! Colliders are stored by index, so that the grid can be
+//! rebuilt every tick from whatever slice they come from and
+//! the answers to its queries point back into that slice.
+
+use crate::hitbox::Collider;
+
+pub struct SpatialGrid {
+    /// Width and height of every cell, in pixels
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    /// The indices of the colliders whose bounds overlap each
+    /// cell, row by row
+    cells: Vec<Vec<usize>>,
+    /// Every collider in the grid, by index
+    colliders: Vec<Collider>,
+}
+
+impl SpatialGrid {
+    /// A grid covering a `width` by `height` area. Colliders
+    /// outside of it are still found, since they're kept in
+    /// the cells along its border
+    pub fn new(width: f32, height: f32, cell_size: f32) -> Self {
+        let columns =
+            (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+
+        Self {
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            colliders: Vec::new(),
+        }
+    }
+
+    /// Replaces the contents of the grid, reusing its memory
+    pub fn rebuild(
+        &mut self,
+        colliders: impl IntoIterator<Item = Collider>,
+    ) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.colliders.clear();
+        self.colliders.extend(colliders);
+
+        for (index, collider) in
+            self.colliders.iter().enumerate()
+        {
+            let rect = collider.bounds();
+            let (left, top) = self.cell_of(rect.x, rect.y);
+            let (right, bottom) = self.cell_of(
+                rect.x + rect.width,
+                rect.y + rect.height,
+            );
+
+            for row in top..=bottom {
+                for column in left..=right {
+                    self.cells[row * self.columns + column]
+                        .push(index);
+                }
+            }
+        }
+    }
+
+    /// The column and row of the cell a point falls in, where
+    /// points outside of the grid go to its closest cell
+    fn cell_of(&self, x: f32, y: f32) -> (usize, usize) {
+        let clamp = |coordinate: f32, cells: usize| {
+            ((coordinate / self.cell_size).floor().max(0.0)
+                as usize)
+                .min(cells - 1)
+        };
+
+        (clamp(x, self.columns), clamp(y, self.rows))
+    }
+
+    /// Calls `f` with the index of every collider in the grid
+    /// that intersects `collider`, once each
+    pub fn for_each_intersecting(
+        &self,
+        collider: &Collider,
+        mut f: impl FnMut(usize),
+    ) {
+        let rect = collider.bounds();
+        let (left, top) = self.cell_of(rect.x, rect.y);
+        let (right, bottom) = self
+            .cell_of(rect.x + rect.width, rect.y + rect.height);
+
+        for row in top..=bottom {
+            for column in left..=right {
+                for &index in
+                    &self.cells[row * self.columns + column]
+                {
+                    let other = &self.colliders[index];
+                    if !other.intersects(collider) {
+                        continue;
+                    }
+
+                    // A collider spanning many cells is met in
+                    // all of them, so only report it from the
+                    // cell where the bounds start overlapping
+                    let other = other.bounds();
+                    let overlap_start = self.cell_of(
+                        other.x.max(rect.x),
+                        other.y.max(rect.y),
+                    );
+                    if overlap_start == (column, row) {
+                        f(index);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether any collider in the grid intersects `collider`
+    pub fn any_intersecting(&self, collider: &Collider) -> bool {
+        let mut found = false;
+        self.for_each_intersecting(collider, |_| found = true);
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetra::{graphics::Rectangle, math::Vec2};
+
+    use super::*;
+
+    fn aabb(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    ) -> Collider {
+        Collider::Aabb(Rectangle::new(x, y, width, height))
+    }
+
+    fn circle(x: f32, y: f32, radius: f32) -> Collider {
+        Collider::Circle {
+            center: Vec2::new(x, y),
+            radius,
+        }
+    }
+
+    /// A grid of 64 pixel cells over an 800 by 800 arena, like
+    /// the game's
+    fn grid_of(colliders: &[Collider]) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(800.0, 800.0, 64.0);
+        grid.rebuild(colliders.iter().copied());
+
+        grid
+    }
+
+    /// Every index reported for `collider`, in order
+    fn found(
+        grid: &SpatialGrid,
+        collider: &Collider,
+    ) -> Vec<usize> {
+        let mut found = Vec::new();
+        grid.for_each_intersecting(collider, |index| {
+            found.push(index)
+        });
+        found.sort_unstable();
+
+        found
+    }
+
+    #[test]
+    fn large_colliders_are_reported_once() {
+        let grid = grid_of(&[
+            aabb(50.0, 50.0, 300.0, 200.0),
+            circle(400.0, 400.0, 150.0),
+        ]);
+
+        // Queries spanning every cell the colliders are in
+        assert_eq!(
+            found(&grid, &aabb(0.0, 0.0, 800.0, 800.0)),
+            [0, 1]
+        );
+        assert_eq!(
+            found(&grid, &aabb(40.0, 40.0, 400.0, 30.0)),
+            [0]
+        );
+        // And ones touching only cells far from where they
+        // start
+        assert_eq!(
+            found(&grid, &circle(340.0, 240.0, 4.0)),
+            [0]
+        );
+        assert_eq!(
+            found(&grid, &circle(530.0, 400.0, 10.0)),
+            [1]
+        );
+        assert!(
+            found(&grid, &circle(700.0, 100.0, 10.0)).is_empty()
+        );
+    }
+
+    #[test]
+    fn colliders_on_cell_borders_are_found() {
+        let grid = grid_of(&[
+            // Starting right on a border
+            aabb(64.0, 64.0, 10.0, 10.0),
+            // Ending right on one
+            aabb(118.0, 0.0, 10.0, 10.0),
+            // Centered on a corner
+            circle(256.0, 256.0, 8.0),
+        ]);
+
+        assert_eq!(found(&grid, &circle(64.0, 64.0, 2.0)), [0]);
+        assert_eq!(found(&grid, &circle(127.0, 5.0, 2.0)), [1]);
+        assert_eq!(found(&grid, &circle(129.0, 5.0, 2.0)), [1]);
+        for (x, y) in
+            [(250.0, 250.0), (262.0, 262.0), (250.0, 262.0)]
+        {
+            assert_eq!(found(&grid, &circle(x, y, 2.0)), [2]);
+        }
+        // Touching doesn't count as intersecting
+        assert!(found(&grid, &aabb(54.0, 64.0, 10.0, 10.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn colliders_off_the_arena_are_found() {
+        let grid = grid_of(&[
+            aabb(-100.0, -100.0, 20.0, 20.0),
+            circle(900.0, 400.0, 10.0),
+            // Reaching into the arena from outside
+            aabb(-50.0, 300.0, 100.0, 20.0),
+        ]);
+
+        assert_eq!(
+            found(&grid, &circle(-90.0, -90.0, 5.0)),
+            [0]
+        );
+        assert_eq!(
+            found(&grid, &circle(905.0, 405.0, 5.0)),
+            [1]
+        );
+        assert_eq!(
+            found(&grid, &circle(-40.0, 310.0, 5.0)),
+            [2]
+        );
+        assert_eq!(found(&grid, &circle(40.0, 310.0, 5.0)), [2]);
+        assert_eq!(
+            found(
+                &grid,
+                &aabb(-1000.0, -1000.0, 3000.0, 3000.0)
+            ),
+            [0, 1, 2]
+        );
+
+        // They share the border cells with colliders inside
+        assert!(!grid.any_intersecting(&circle(5.0, 5.0, 5.0)));
+        assert!(
+            grid.any_intersecting(&circle(-85.0, -85.0, 1.0))
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use std::time::Instant;
 
 use arrayvec::ArrayVec;
-use rand::{seq::SliceRandom, Rng};
 use tetra::{graphics::Texture, Context};
 
 use crate::{
+    animation::HumanoidAnimation,
     humanoid::HumanoidType,
     resources::{
         self, BADASS_GRUNTS, BASIC_GRUNTS, BOSS, STRONGER_GRUNTS,
@@ -14,23 +14,24 @@ use crate::{
 /// Loads all grunt sprites into memory to avoid
 /// recreating the texture when spawning enemies
 pub struct GruntTextures {
-    basic_grunts: [Texture; BASIC_GRUNTS.len()],
-    stronger_grunts: [Texture; STRONGER_GRUNTS.len()],
-    badass_grunts: [Texture; BADASS_GRUNTS.len()],
-    boss: Texture,
+    basic_grunts: [HumanoidAnimation; BASIC_GRUNTS.len()],
+    stronger_grunts: [HumanoidAnimation; STRONGER_GRUNTS.len()],
+    badass_grunts: [HumanoidAnimation; BADASS_GRUNTS.len()],
+    boss: HumanoidAnimation,
 }
 
 impl GruntTextures {
     fn load_textures<const N: usize>(
         ctx: &mut Context,
         sprites: &[&[u8]],
-    ) -> [Texture; N] {
-        let mut textures: ArrayVec<Texture, N> = ArrayVec::new();
+    ) -> [HumanoidAnimation; N] {
+        let mut textures: ArrayVec<HumanoidAnimation, N> =
+            ArrayVec::new();
 
         for sprite in sprites {
-            textures.push(
+            textures.push(HumanoidAnimation::new(
                 Texture::from_encoded(ctx, sprite).unwrap(),
-            );
+            ));
         }
 
         textures.into_inner().unwrap()
@@ -49,7 +50,9 @@ impl GruntTextures {
                 ctx,
                 BADASS_GRUNTS,
             ),
-            boss: Texture::from_encoded(ctx, BOSS).unwrap(),
+            boss: HumanoidAnimation::new(
+                Texture::from_encoded(ctx, BOSS).unwrap(),
+            ),
         };
 
         println!(
@@ -60,28 +63,27 @@ impl GruntTextures {
         textures
     }
 
-    /// Choose a random texture of the given enemy kind
-    pub fn choose_enemy_from_kind<R: Rng>(
-        &self,
+    /// The animation of the given sprite of an enemy kind
+    pub fn animation_mut(
+        &mut self,
         kind: HumanoidType,
-        rng: &mut R,
-    ) -> Texture {
+        sprite: usize,
+    ) -> &mut HumanoidAnimation {
         match kind {
             HumanoidType::Player => unreachable!(
                 "An enemy cannot have the player's sprite"
             ),
             HumanoidType::BasicEnemy => {
-                self.basic_grunts.choose(rng).unwrap()
+                &mut self.basic_grunts[sprite]
             }
             HumanoidType::StrongerEnemy => {
-                self.stronger_grunts.choose(rng).unwrap()
+                &mut self.stronger_grunts[sprite]
             }
             HumanoidType::BadassEnemy => {
-                self.badass_grunts.choose(rng).unwrap()
+                &mut self.badass_grunts[sprite]
             }
-            HumanoidType::Boss => &self.boss,
+            HumanoidType::Boss => &mut self.boss,
         }
-        .clone() // Texture is an Rc so this clone is cheap
     }
 }
 
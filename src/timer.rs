@@ -1,28 +1,154 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+use crate::clock::GameClock;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Timer {
     interval: Duration,
-    last_ticked: Instant,
+    /// The moment (in game time) this timer was last reset
+    last_ticked: Duration,
 }
 
 impl Timer {
-    pub fn start_now_with_interval(interval: Duration) -> Self {
+    pub fn start_now_with_interval(
+        clock: &GameClock,
+        interval: Duration,
+    ) -> Self {
         Self {
             interval,
-            last_ticked: Instant::now(),
+            last_ticked: clock.now(),
         }
     }
 
-    pub fn is_ready(&self) -> bool {
-        self.last_ticked.elapsed() >= self.interval
+    /// Changes how long the timer takes to be ready, counting
+    /// from when it was last reset
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn is_ready(&self, clock: &GameClock) -> bool {
+        self.elapsed(clock) >= self.interval
+    }
+
+    pub fn reset(&mut self, clock: &GameClock) {
+        self.last_ticked = clock.now()
     }
 
-    pub fn reset(&mut self) {
-        self.last_ticked = Instant::now()
+    pub fn elapsed(&self, clock: &GameClock) -> Duration {
+        clock.now().saturating_sub(self.last_ticked)
     }
 
-    pub fn elapsed(&self) -> Duration {
-        self.last_ticked.elapsed()
+    /// How long until the timer is ready
+    pub fn remaining(&self, clock: &GameClock) -> Duration {
+        self.interval.saturating_sub(self.elapsed(clock))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestep::TICK_DURATION;
+
+    const INTERVAL: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn is_ready_once_the_interval_passed() {
+        let mut clock = GameClock::new();
+        let mut timer =
+            Timer::start_now_with_interval(&clock, INTERVAL);
+
+        clock.advance(Duration::from_millis(499));
+        assert!(!timer.is_ready(&clock));
+        assert_eq!(
+            timer.remaining(&clock),
+            Duration::from_millis(1)
+        );
+
+        clock.advance(Duration::from_millis(1));
+        assert!(timer.is_ready(&clock));
+        assert_eq!(timer.remaining(&clock), Duration::ZERO);
+
+        timer.reset(&clock);
+        assert!(!timer.is_ready(&clock));
+        assert_eq!(timer.elapsed(&clock), Duration::ZERO);
+    }
+
+    #[test]
+    fn does_not_run_while_paused() {
+        let mut clock = GameClock::new();
+        let timer =
+            Timer::start_now_with_interval(&clock, INTERVAL);
+
+        clock.set_paused(true);
+        clock.advance(Duration::from_secs(10));
+
+        assert!(!timer.is_ready(&clock));
+        assert_eq!(timer.remaining(&clock), INTERVAL);
+    }
+
+    #[test]
+    fn jumping_ahead_fires_like_stepping_through() {
+        const TICKS: u32 = 90;
+        let intervals = [100, 250, 500, 1000, 1500, 2000]
+            .map(Duration::from_millis);
+
+        let mut stepped = GameClock::new();
+        let mut jumped = GameClock::new();
+        let timers = intervals.map(|interval| {
+            Timer::start_now_with_interval(&stepped, interval)
+        });
+
+        for _ in 0..TICKS {
+            stepped.advance(TICK_DURATION);
+        }
+        jumped.advance(TICK_DURATION * TICKS);
+
+        assert_eq!(stepped.now(), jumped.now());
+        for timer in timers {
+            assert_eq!(
+                timer.is_ready(&stepped),
+                timer.is_ready(&jumped)
+            );
+            assert_eq!(
+                timer.remaining(&stepped),
+                timer.remaining(&jumped)
+            );
+        }
+
+        // 90 ticks are a hair short of 1.5 seconds
+        let ready: Vec<_> = timers
+            .iter()
+            .map(|timer| timer.is_ready(&jumped))
+            .collect();
+        assert_eq!(
+            ready,
+            [true, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn jumping_past_the_interval_is_ready_right_away() {
+        let mut clock = GameClock::new();
+        let timer =
+            Timer::start_now_with_interval(&clock, INTERVAL);
+
+        clock.advance(INTERVAL * 10);
+
+        assert!(timer.is_ready(&clock));
+        assert_eq!(timer.elapsed(&clock), INTERVAL * 10);
+    }
+
+    #[test]
+    fn follows_the_time_scale() {
+        let mut clock = GameClock::new();
+        let timer =
+            Timer::start_now_with_interval(&clock, INTERVAL);
+
+        clock.set_time_scale(0.5);
+        clock.advance(Duration::from_millis(999));
+        assert!(!timer.is_ready(&clock));
+
+        clock.advance(Duration::from_millis(1));
+        assert!(timer.is_ready(&clock));
     }
 }
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+/// How many times per second the simulation is advanced,
+/// regardless of how fast frames are drawn
+pub const TICKS_PER_SECOND: u32 = 60;
+
+/// How long a single tick of the simulation lasts
+pub const TICK_DURATION: Duration = Duration::from_nanos(
+    1_000_000_000 / TICKS_PER_SECOND as u64,
+);
+
+/// The most ticks we'll run to catch up on a single frame, so
+/// that a very slow frame can't make us fall further and
+/// further behind
+const MAX_TICKS_PER_FRAME: u32 = 8;
+
+/// Accumulates the time taken by each frame and hands it out
+/// in fixed-size ticks.
+///
+/// This keeps the game logic independent of the framerate: a
+/// 144 Hz display runs a tick on some frames and none on
+/// others, while a 30 Hz display runs two ticks every frame.
+pub struct FixedTimestep {
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self {
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    /// Adds the time the last frame took, returning how many
+    /// ticks should be run to catch up with it
+    pub fn advance(&mut self, frame_time: Duration) -> u32 {
+        self.accumulator += frame_time;
+
+        let mut ticks = 0;
+        while self.accumulator >= TICK_DURATION {
+            if ticks == MAX_TICKS_PER_FRAME {
+                // We're too far behind, so drop the leftover
+                // time instead of trying to catch up with it
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+
+            self.accumulator -= TICK_DURATION;
+            ticks += 1;
+        }
+
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use tetra::math::Vec2;
+
+    use super::*;
+    use crate::{
+        difficulty::Difficulty, input::InputFrame,
+        simulation::Simulation, waves,
+    };
+
+    /// How many ticks every run lasts, which is 20 seconds
+    const TICKS: u32 = 1200;
+
+    /// The same input for the same tick, whatever the frame
+    /// rate: running around in a square while firing in circles
+    fn input(tick: u32) -> InputFrame {
+        const MOVES: [(f32, f32); 4] =
+            [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)];
+
+        let (x, y) = MOVES[(tick / 90) as usize % MOVES.len()];
+        InputFrame {
+            movement: Vec2::new(x, y),
+            fire_angle: Some((tick * 7 % 360) as f32),
+        }
+    }
+
+    /// Plays a run with frames drawn `frame_rate` times per
+    /// second, until it's [`TICKS`] ticks long
+    fn play(frame_rate: u32) -> Simulation {
+        let mut sim = Simulation::new(
+            42,
+            Rc::new(waves::default_waves()),
+            Difficulty::Normal,
+        );
+        let mut timestep = FixedTimestep::new();
+        let frame_time = Duration::from_secs(1) / frame_rate;
+
+        let mut tick = 0;
+        while tick < TICKS {
+            for _ in 0..timestep.advance(frame_time) {
+                if tick < TICKS {
+                    sim.tick(&input(tick));
+                    tick += 1;
+                }
+            }
+        }
+
+        sim
+    }
+
+    #[test]
+    fn ticks_add_up_to_the_frame_time() {
+        for frame_rate in [30, 60, 144] {
+            let mut timestep = FixedTimestep::new();
+            let frame_time = Duration::from_secs(1) / frame_rate;
+
+            let ticks: u32 = (0..frame_rate * 10)
+                .map(|_| timestep.advance(frame_time))
+                .sum();
+
+            // Frame times are rounded down to the nanosecond,
+            // which can cost the last tick
+            let expected = TICKS_PER_SECOND * 10;
+            assert!(
+                (expected - 1..=expected).contains(&ticks),
+                "{} ticks in 10 seconds at {} Hz",
+                ticks,
+                frame_rate
+            );
+        }
+    }
+
+    #[test]
+    fn slow_frames_are_capped() {
+        let mut timestep = FixedTimestep::new();
+
+        assert_eq!(
+            timestep.advance(Duration::from_secs(1)),
+            MAX_TICKS_PER_FRAME
+        );
+        // The time that couldn't be caught up with is dropped
+        assert_eq!(timestep.advance(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn runs_play_the_same_at_any_frame_rate() {
+        let reference = play(60);
+        let reference_player =
+            reference.player_manager().player();
+        assert!(
+            !reference.enemy_mgr().enemies.is_empty(),
+            "the run should have enemies to compare"
+        );
+
+        for frame_rate in [30, 144] {
+            let sim = play(frame_rate);
+            let player = sim.player_manager().player();
+
+            assert_eq!(
+                player.position, reference_player.position,
+                "player position at {frame_rate} Hz"
+            );
+            assert_eq!(
+                player.hearts, reference_player.hearts,
+                "hearts at {frame_rate} Hz"
+            );
+            assert_eq!(
+                sim.game_score(),
+                reference.game_score(),
+                "score at {frame_rate} Hz"
+            );
+            assert_eq!(
+                sim.current_wave(),
+                reference.current_wave()
+            );
+
+            let positions = |sim: &Simulation| {
+                sim.enemy_mgr()
+                    .enemies
+                    .iter()
+                    .map(|enemy| enemy.position)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                positions(&sim),
+                positions(&reference),
+                "enemy positions at {frame_rate} Hz"
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::{rc::Rc, time::Duration};
+
+use rand::{rngs::OsRng, Rng};
+use tetra::{
+    graphics::text::Text, input, math::Vec2, time, window,
+    Context,
+};
+
+use crate::{
+    bot,
+    difficulty::Difficulty,
+    highscores::HighScores,
+    input::InputSource,
+    menu::Menu,
+    playing::Playing,
+    scene::{Scene, Shared, Transition},
+    settings::Settings,
+    simulation::Simulation,
+    timer::Timer,
+    timestep::FixedTimestep,
+    waves::Waves,
+    HEIGHT, VERSION, WIDTH,
+};
+
+/// How long the title screen waits for a key before the bot
+/// starts playing
+const ATTRACT_MODE_DELAY: Duration = Duration::from_secs(10);
+
+/// The entries of the title screen
+#[derive(Clone, Copy, PartialEq)]
+enum TitleAction {
+    Start,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+/// A run played by the bot while the title screen is idle
+struct Demo {
+    simulation: Simulation,
+    timestep: FixedTimestep,
+}
+
+impl Demo {
+    fn new(waves: Rc<Waves>) -> Self {
+        Self {
+            simulation: Simulation::new(
+                OsRng.gen(),
+                waves,
+                Difficulty::Normal,
+            ),
+            timestep: FixedTimestep::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context) {
+        let ticks =
+            self.timestep.advance(time::get_delta_time(ctx));
+
+        for _ in 0..ticks {
+            // Keep the show going once the bot dies
+            if self.simulation.is_game_over() {
+                *self = Demo::new(self.simulation.waves());
+                break;
+            }
+
+            let input = bot::play(&self.simulation);
+            self.simulation.tick(&input);
+        }
+    }
+}
+
+/// The main menu of the game, which turns into a demo played
+/// by a bot when left idle
+pub struct Title {
+    menu: Menu<TitleAction>,
+    version_text: Text,
+    /// Shown over the demo
+    hint_text: Text,
+    /// Restarted whenever a key is pressed
+    idle_timer: Timer,
+    demo: Option<Demo>,
+}
+
+impl Title {
+    pub fn new(ctx: &mut Context, shared: &Shared) -> Self {
+        window::set_title(ctx, "Endless Trial");
+
+        Self {
+            menu: Menu::new(
+                ctx,
+                shared,
+                "Endless Trial",
+                &[
+                    (TitleAction::Start, "Start"),
+                    (TitleAction::Settings, "Settings"),
+                    (TitleAction::HighScores, "High Scores"),
+                    (TitleAction::Quit, "Quit"),
+                ],
+            ),
+            version_text: Text::new(
+                format!("v{VERSION}"),
+                shared.fonts.small.clone(),
+            ),
+            hint_text: Text::new(
+                "Press any key",
+                shared.fonts.small.clone(),
+            ),
+            idle_timer: Timer::start_now_with_interval(
+                &shared.ui_clock,
+                ATTRACT_MODE_DELAY,
+            ),
+            demo: None,
+        }
+    }
+
+    /// Draws `text` centered horizontally at the given height
+    fn draw_centered(
+        ctx: &mut Context,
+        text: &mut Text,
+        y: f32,
+    ) {
+        let width = text
+            .get_bounds(ctx)
+            .map_or(0.0, |bounds| bounds.width);
+
+        text.draw(
+            ctx,
+            Vec2::new((WIDTH as f32 - width) / 2.0, y),
+        );
+    }
+}
+
+impl Scene for Title {
+    fn update(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result<Transition> {
+        let any_key_pressed =
+            input::get_keys_pressed(ctx).next().is_some();
+        if any_key_pressed {
+            self.idle_timer.reset(&shared.ui_clock);
+        }
+
+        if let Some(demo) = &mut self.demo {
+            // The key that stops the demo shouldn't also pick
+            // an entry of the menu
+            if any_key_pressed {
+                self.demo = None;
+            } else {
+                demo.update(ctx);
+            }
+
+            return Ok(Transition::None);
+        }
+
+        if self.idle_timer.is_ready(&shared.ui_clock) {
+            self.demo = Some(Demo::new(shared.waves.clone()));
+            return Ok(Transition::None);
+        }
+
+        let transition = match self.menu.update(ctx) {
+            Some(TitleAction::Start) => {
+                Transition::Switch(Box::new(Playing::new(
+                    shared,
+                    OsRng.gen(),
+                    InputSource::Keyboard(None),
+                )))
+            }
+            Some(TitleAction::Settings) => Transition::Push(
+                Box::new(Settings::new(ctx, shared)),
+            ),
+            Some(TitleAction::HighScores) => Transition::Push(
+                Box::new(HighScores::new(ctx, shared)),
+            ),
+            Some(TitleAction::Quit) => Transition::Quit,
+            None => Transition::None,
+        };
+
+        Ok(transition)
+    }
+
+    fn draw(
+        &mut self,
+        ctx: &mut Context,
+        shared: &mut Shared,
+    ) -> tetra::Result {
+        if let Some(demo) = &self.demo {
+            shared.renderer.draw(ctx, &demo.simulation);
+            Self::draw_centered(
+                ctx,
+                &mut self.hint_text,
+                HEIGHT as f32 - 80.0,
+            );
+
+            return Ok(());
+        }
+
+        shared.renderer.draw_background(ctx);
+        self.menu.draw(ctx);
+
+        let below_menu =
+            (HEIGHT as f32 + self.menu.height()) / 2.0 + 8.0;
+        Self::draw_centered(
+            ctx,
+            &mut self.version_text,
+            below_menu,
+        );
+
+        Ok(())
+    }
+
+    fn on_resume(&mut self, shared: &mut Shared) {
+        self.idle_timer.reset(&shared.ui_clock);
+    }
+
+    fn simulation(&self) -> Option<&Simulation> {
+        self.demo.as_ref().map(|demo| &demo.simulation)
+    }
+}
@@ -0,0 +1,444 @@
+//! The waves a run goes through, described in a TOML file.
+//!
+//! The game ships with `resources/waves.toml`, which also
+//! documents every setting, and `--waves <file>` plays a
+//! different one.
+
+use std::{
+    collections::BTreeMap, fs, path::Path, time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::{humanoid::HumanoidType, resources};
+
+/// Every kind of enemy, as written in the wave file and in
+/// console commands
+pub const ENEMY_NAMES: [(&str, HumanoidType); 4] = [
+    ("basic", HumanoidType::BasicEnemy),
+    ("stronger", HumanoidType::StrongerEnemy),
+    ("badass", HumanoidType::BadassEnemy),
+    ("boss", HumanoidType::Boss),
+];
+
+/// The longest any duration in the file can be, which is a
+/// day. Durations are scaled by the difficulty, so they need
+/// to stay far from what a [`Duration`] can hold
+const MAX_SECONDS: f64 = 24.0 * 60.0 * 60.0;
+
+pub struct Wave {
+    /// How long the wave lasts, unless it's the last one
+    pub duration: Duration,
+    /// How long to wait between spawning two enemies
+    pub spawn_interval: Duration,
+    /// How many enemies can be alive at once, if there's a
+    /// limit at all
+    pub max_enemies: Option<usize>,
+    /// How long to wait between spawning two power-ups
+    pub power_up_interval: Duration,
+    /// Enemy types and their relative chance of being spawned
+    pub enemies: Vec<(HumanoidType, f32)>,
+    /// When bosses spawn, counting from the start of the wave,
+    /// in order
+    pub bosses: Vec<Duration>,
+}
+
+/// Everything a run goes through
+pub struct Waves {
+    /// The authored waves, of which there's at least one
+    pub list: Vec<Wave>,
+    /// How the game keeps getting harder after the last
+    /// authored wave. Without it, the last wave goes on forever
+    pub endless: Option<Endless>,
+}
+
+/// The difficulty curve past the last authored wave, which is
+/// played again and again, a tier harder every time
+pub struct Endless {
+    /// How many times more often enemies spawn
+    pub spawn_rate: Curve,
+    /// How many times more hearts enemies have
+    pub enemy_health: Curve,
+    /// How many times faster enemy cannonballs fly
+    pub projectile_speed: Curve,
+    /// The chance of an enemy spawning as the next kind up
+    pub elite_chance: Curve,
+    /// How many bosses spawn on top of the wave's own
+    pub bosses: Curve,
+}
+
+impl Waves {
+    /// The wave at `index`, where the last authored wave stands
+    /// in for every endless one past it
+    pub fn get(&self, index: u16) -> &Wave {
+        let last = self.list.len() - 1;
+        &self.list[last.min(index as usize)]
+    }
+
+    /// Whether there's a wave at `index`, which there always is
+    /// once the waves are endless
+    pub fn has_wave(&self, index: u16) -> bool {
+        self.endless.is_some()
+            || (index as usize) < self.list.len()
+    }
+}
+
+/// A value that grows by the same amount every tier, until it
+/// reaches its cap
+#[derive(Clone, Copy)]
+pub struct Curve {
+    /// The value before the first endless tier
+    start: f64,
+    per_tier: f64,
+    max: f64,
+}
+
+impl Curve {
+    pub fn at(&self, tier: u16) -> f64 {
+        (self.start + self.per_tier * tier as f64).min(self.max)
+    }
+}
+
+/// The waves as written in the file, before they're checked
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWaves {
+    wave: Vec<RawWave>,
+    endless: Option<RawEndless>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWave {
+    duration: f64,
+    spawn_interval: f64,
+    max_enemies: Option<usize>,
+    power_up_interval: f64,
+    /// Weights by enemy name, which are checked against
+    /// [`ENEMY_NAMES`]
+    enemies: BTreeMap<String, f64>,
+    #[serde(default)]
+    bosses: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawEndless {
+    spawn_rate: RawCurve,
+    enemy_health: RawCurve,
+    projectile_speed: RawCurve,
+    elite_chance: RawCurve,
+    bosses: RawCurve,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCurve {
+    per_tier: f64,
+    max: f64,
+}
+
+/// The waves the game ships with
+pub fn default_waves() -> Waves {
+    parse(resources::WAVES)
+        .expect("the built-in waves.toml should be valid")
+}
+
+/// Reads the waves from a file, with an error pointing at
+/// what's wrong with it if they don't make sense
+pub fn load(path: &Path) -> Result<Waves, String> {
+    let source = fs::read_to_string(path).map_err(|err| {
+        format!("could not read {}: {err}", path.display())
+    })?;
+
+    parse(&source)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn parse(source: &str) -> Result<Waves, String> {
+    let raw: RawWaves =
+        toml::from_str(source).map_err(|err| err.to_string())?;
+
+    // The current wave is kept in a `u16`
+    if raw.wave.is_empty() || raw.wave.len() > u16::MAX as usize
+    {
+        return Err(format!(
+            "there must be between 1 and {} waves",
+            u16::MAX
+        ));
+    }
+
+    let list = raw
+        .wave
+        .iter()
+        .enumerate()
+        .map(|(index, wave)| {
+            check_wave(wave).map_err(|err| {
+                format!("wave {}: {err}", index + 1)
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let endless = raw
+        .endless
+        .as_ref()
+        .map(|endless| {
+            check_endless(endless)
+                .map_err(|err| format!("endless: {err}"))
+        })
+        .transpose()?;
+
+    Ok(Waves { list, endless })
+}
+
+fn check_endless(raw: &RawEndless) -> Result<Endless, String> {
+    let infinite = f64::INFINITY;
+
+    Ok(Endless {
+        spawn_rate: check_curve(
+            "spawn_rate",
+            &raw.spawn_rate,
+            1.0,
+            infinite,
+        )?,
+        enemy_health: check_curve(
+            "enemy_health",
+            &raw.enemy_health,
+            1.0,
+            infinite,
+        )?,
+        projectile_speed: check_curve(
+            "projectile_speed",
+            &raw.projectile_speed,
+            1.0,
+            infinite,
+        )?,
+        elite_chance: check_curve(
+            "elite_chance",
+            &raw.elite_chance,
+            0.0,
+            1.0,
+        )?,
+        bosses: check_curve(
+            "bosses",
+            &raw.bosses,
+            0.0,
+            infinite,
+        )?,
+    })
+}
+
+/// Checks a curve that starts at `start` and whose cap can't
+/// go past `limit`, which may be infinite
+fn check_curve(
+    key: &str,
+    raw: &RawCurve,
+    start: f64,
+    limit: f64,
+) -> Result<Curve, String> {
+    let RawCurve { per_tier, max } = *raw;
+
+    if !(per_tier >= 0.0 && per_tier.is_finite()) {
+        return Err(format!(
+            "`{key}.per_tier` can't be negative, found {per_tier}"
+        ));
+    }
+
+    if !(max.is_finite() && (start..=limit).contains(&max)) {
+        let expected = if limit.is_finite() {
+            format!("between {start} and {limit}")
+        } else {
+            format!("at least {start}")
+        };
+
+        return Err(format!(
+            "`{key}.max` must be {expected}, found {max}"
+        ));
+    }
+
+    Ok(Curve {
+        start,
+        per_tier,
+        max,
+    })
+}
+
+/// Checks a number of seconds that must be above zero, and no
+/// more than [`MAX_SECONDS`]
+fn check_seconds(
+    key: &str,
+    seconds: f64,
+) -> Result<Duration, String> {
+    if !(seconds > 0.0 && seconds <= MAX_SECONDS) {
+        return Err(format!(
+            "`{key}` must be a number of seconds above zero and up to {MAX_SECONDS}, found {seconds}"
+        ));
+    }
+
+    to_duration(seconds)
+}
+
+/// Turns a number of seconds that's known not to be negative
+/// into a duration, failing if it's too long to be one
+fn to_duration(seconds: f64) -> Result<Duration, String> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("{seconds} seconds is too long"))
+}
+
+fn check_wave(raw: &RawWave) -> Result<Wave, String> {
+    let duration = check_seconds("duration", raw.duration)?;
+
+    if raw.max_enemies == Some(0) {
+        return Err("`max_enemies` must be at least 1".into());
+    }
+
+    let mut bosses = raw
+        .bosses
+        .iter()
+        .map(|&at| {
+            if !(0.0..duration.as_secs_f64()).contains(&at) {
+                return Err(format!(
+                    "a boss at {at}s would spawn outside of the wave"
+                ));
+            }
+            to_duration(at)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    bosses.sort();
+
+    Ok(Wave {
+        duration,
+        spawn_interval: check_seconds(
+            "spawn_interval",
+            raw.spawn_interval,
+        )?,
+        max_enemies: raw.max_enemies,
+        power_up_interval: check_seconds(
+            "power_up_interval",
+            raw.power_up_interval,
+        )?,
+        enemies: check_enemies(&raw.enemies)?,
+        bosses,
+    })
+}
+
+fn check_enemies(
+    weights: &BTreeMap<String, f64>,
+) -> Result<Vec<(HumanoidType, f32)>, String> {
+    if let Some(name) = weights.keys().find(|name| {
+        !ENEMY_NAMES.iter().any(|(known, _)| known == name)
+    }) {
+        return Err(format!(
+            "unknown enemy `{name}`, expected one of: {}",
+            ENEMY_NAMES.map(|(name, _)| name).join(", ")
+        ));
+    }
+
+    let enemies = ENEMY_NAMES
+        .iter()
+        .filter_map(|&(name, kind)| {
+            weights.get(name).map(|&weight| (kind, weight))
+        })
+        .map(|(kind, weight)| {
+            if !(weight >= 0.0 && weight.is_finite()) {
+                return Err(format!(
+                    "enemy weights can't be negative, found {weight}"
+                ));
+            }
+            Ok((kind, weight as f32))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if enemies.iter().all(|&(_, weight)| weight == 0.0) {
+        return Err(
+            "at least one enemy needs a weight above zero"
+                .into(),
+        );
+    }
+
+    Ok(enemies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file with a single wave, with `extra` added to it
+    fn single_wave(extra: &str) -> String {
+        format!(
+            "[[wave]]\n\
+             duration = 30\n\
+             spawn_interval = 1.5\n\
+             power_up_interval = 3\n\
+             enemies = {{ basic = 1 }}\n\
+             {extra}\n"
+        )
+    }
+
+    fn error_of(source: &str) -> String {
+        match parse(source) {
+            Ok(_) => panic!("the waves should be rejected"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn built_in_waves_are_valid() {
+        let waves = default_waves();
+
+        assert!(!waves.list.is_empty());
+        assert!(waves.endless.is_some());
+    }
+
+    #[test]
+    fn enemies_are_only_capped_when_asked_to() {
+        let uncapped = parse(&single_wave("")).unwrap();
+        assert_eq!(uncapped.list[0].max_enemies, None);
+
+        let capped =
+            parse(&single_wave("max_enemies = 12")).unwrap();
+        assert_eq!(capped.list[0].max_enemies, Some(12));
+
+        assert_eq!(
+            error_of(&single_wave("max_enemies = 0")),
+            "wave 1: `max_enemies` must be at least 1"
+        );
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        let err = error_of(&single_wave("speed = 2"));
+        assert!(
+            err.contains("unknown field `speed`"),
+            "{}",
+            err
+        );
+
+        assert_eq!(
+            error_of(&single_wave("").replace("basic", "goblin")),
+            "wave 1: unknown enemy `goblin`, expected one of: basic, stronger, badass, boss"
+        );
+        assert_eq!(
+            error_of("wave = []"),
+            "there must be between 1 and 65535 waves"
+        );
+    }
+
+    #[test]
+    fn huge_durations_are_rejected() {
+        let source = single_wave("").replace("30", "1e30");
+
+        assert_eq!(
+            error_of(&source),
+            "wave 1: `duration` must be a number of seconds above zero and up to 86400, found 1000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn huge_boss_times_are_rejected() {
+        assert_eq!(
+            error_of(&single_wave("bosses = [10, 1e30]")),
+            "wave 1: a boss at 1000000000000000000000000000000s would spawn outside of the wave"
+        );
+    }
+}